@@ -131,7 +131,7 @@ fn benchmark_formatter_mode_comparison(c: &mut Criterion) {
 
     c.bench_function("formatter_plural_mode", |b| {
         b.iter(|| {
-            let mut formatter = XCStringFormatter::new(FormatterMode::Plural);
+            let mut formatter = XCStringFormatter::new(FormatterMode::Plural("name1".to_string()));
             let _result = formatter.format(black_box(&element));
         });
     });
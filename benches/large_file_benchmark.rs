@@ -15,25 +15,29 @@ fn generate_large_test_data(strings_count: usize) -> LocalizableICUStrings {
         // 英語のメッセージ
         messages.insert("en".to_string(), LocalizableICUMessageValue {
             value: format!("Hello, {{name_{}}}! You have {{count_{}}} messages.", i, i),
-            state: "translated".to_string(),
+            state: Some("translated".to_string()),
+            ..Default::default()
         });
         
         // 日本語のメッセージ
         messages.insert("ja".to_string(), LocalizableICUMessageValue {
             value: format!("こんにちは、{{name_{}}}さん！{{count_{}}}件のメッセージがあります。", i, i),
-            state: "translated".to_string(),
+            state: Some("translated".to_string()),
+            ..Default::default()
         });
         
         // 韓国語のメッセージ
         messages.insert("ko".to_string(), LocalizableICUMessageValue {
             value: format!("안녕하세요, {{name_{}}}님! {{count_{}}}개의 메시지가 있습니다.", i, i),
-            state: "translated".to_string(),
+            state: Some("translated".to_string()),
+            ..Default::default()
         });
         
         strings.push(LocalizableICUString {
             key,
             messages,
             comment: Some(format!("Generated test message {}", i)),
+            ..Default::default()
         });
     }
     
@@ -50,19 +54,22 @@ fn generate_large_test_data_with_plurals(strings_count: usize) -> LocalizableICU
         // 英語の複数形メッセージ
         messages.insert("en".to_string(), LocalizableICUMessageValue {
             value: format!("You have {{count_{}, plural, =0 {{no messages}} =1 {{one message}} other {{# messages}}}}.", i),
-            state: "translated".to_string(),
+            state: Some("translated".to_string()),
+            ..Default::default()
         });
         
         // 日本語の複数形メッセージ
         messages.insert("ja".to_string(), LocalizableICUMessageValue {
             value: format!("{{count_{}, plural, =0 {{メッセージがありません}} =1 {{1件のメッセージがあります}} other {{#件のメッセージがあります}}}}。", i),
-            state: "translated".to_string(),
+            state: Some("translated".to_string()),
+            ..Default::default()
         });
         
         strings.push(LocalizableICUString {
             key,
             messages,
             comment: Some(format!("Generated plural test message {}", i)),
+            ..Default::default()
         });
     }
     
@@ -79,19 +86,22 @@ fn generate_large_test_data_with_selects(strings_count: usize) -> LocalizableICU
         // 英語のselectメッセージ
         messages.insert("en".to_string(), LocalizableICUMessageValue {
             value: format!("{{gender_{}, select, male {{He}} female {{She}} other {{They}}}} has {{count_{}}} messages.", i, i),
-            state: "translated".to_string(),
+            state: Some("translated".to_string()),
+            ..Default::default()
         });
         
         // 日本語のselectメッセージ
         messages.insert("ja".to_string(), LocalizableICUMessageValue {
             value: format!("{{gender_{}, select, male {{彼}} female {{彼女}} other {{彼ら}}}}は{{count_{}}}件のメッセージを持っています。", i, i),
-            state: "translated".to_string(),
+            state: Some("translated".to_string()),
+            ..Default::default()
         });
         
         strings.push(LocalizableICUString {
             key,
             messages,
             comment: Some(format!("Generated select test message {}", i)),
+            ..Default::default()
         });
     }
     
@@ -1,16 +1,39 @@
+use crate::converter::number_format::NumberFormat;
 use icu_messageformat_parser::{self, AstElement};
 use std::collections::HashMap;
 use std::fmt::Write;
 
+/// Tags that `markdown_delimiters` knows how to translate.
+pub const MARKDOWN_TAGS: &[&str] = &["b", "i", "link"];
+
 #[derive(Debug)]
 pub enum FormatterMode {
     StringUnit,
-    Plural,
+    /// Formatting the branches of the plural for the named argument, where `#`
+    /// and the argument itself become the plural placeholder (`%arg` by default).
+    Plural(String),
 }
 
 pub struct XCStringFormatter {
     formatter_mode: FormatterMode,
     argument_positions: HashMap<String, usize>,
+    argument_specifiers: HashMap<String, String>,
+    argument_offsets: HashMap<String, i64>,
+    /// Translator notes for number styles a specifier can't fully express.
+    argument_hints: HashMap<String, String>,
+    /// Specifiers from the key's `placeholders` declarations, which take
+    /// precedence over those the ICU syntax implies.
+    declared_specifiers: HashMap<String, String>,
+    /// Translate `<b>`, `<i>` and `<link>` tags into Markdown instead of
+    /// rejecting them.
+    markdown_tags: bool,
+    /// The select argument that becomes device variations, which takes no
+    /// position of its own.
+    device_argument: Option<String>,
+    /// What `#` and the plural's own argument become inside its branches:
+    /// `%arg` for a substitution, or a specifier such as `%lld` for plural
+    /// variations of the whole localization.
+    plural_placeholder: String,
     next_position: usize,
 }
 
@@ -19,36 +42,265 @@ impl XCStringFormatter {
         XCStringFormatter {
             formatter_mode: mode,
             argument_positions: HashMap::with_capacity(16),
+            argument_specifiers: HashMap::with_capacity(16),
+            argument_offsets: HashMap::new(),
+            argument_hints: HashMap::new(),
+            declared_specifiers: HashMap::new(),
+            markdown_tags: false,
+            device_argument: None,
+            plural_placeholder: "%arg".to_string(),
             next_position: 1,
         }
     }
 
+    /// Creates a formatter that reuses argument positions and specifiers assigned
+    /// elsewhere, typically from the source language message of the same key, so
+    /// that every localization uses the same specifier for a position.
+    pub fn with_positions(
+        mode: FormatterMode,
+        argument_positions: HashMap<String, usize>,
+        argument_specifiers: HashMap<String, String>,
+    ) -> Self {
+        let next_position = argument_positions.values().max().map_or(1, |max| max + 1);
+        XCStringFormatter {
+            formatter_mode: mode,
+            argument_positions,
+            argument_specifiers,
+            argument_offsets: HashMap::new(),
+            argument_hints: HashMap::new(),
+            declared_specifiers: HashMap::new(),
+            markdown_tags: false,
+            device_argument: None,
+            plural_placeholder: "%arg".to_string(),
+            next_position,
+        }
+    }
+
+    /// Enables translating tags into Markdown, see `markdown_delimiters`.
+    pub fn with_markdown_tags(mut self, markdown_tags: bool) -> Self {
+        self.markdown_tags = markdown_tags;
+        self
+    }
+
+    /// Uses the declared specifiers for their arguments wherever they appear.
+    pub fn with_declared_specifiers(mut self, declared_specifiers: HashMap<String, String>) -> Self {
+        for (name, specifier) in &declared_specifiers {
+            self.argument_specifiers.insert(name.clone(), specifier.clone());
+        }
+        self.declared_specifiers = declared_specifiers;
+        self
+    }
+
+    /// Sets the select argument converted into device variations, see
+    /// `ConverterOptions::device_argument`.
+    pub fn with_device_argument(mut self, device_argument: Option<String>) -> Self {
+        self.device_argument = device_argument;
+        self
+    }
+
+    /// Assigns positions to the arguments of `elements`, including those inside
+    /// plural and select branches, in the same order `format` would, without
+    /// producing any output.
+    pub fn assign_positions(&mut self, elements: &[AstElement]) -> Result<(), String> {
+        self.assign_positions_within(elements, None)
+    }
+
+    fn assign_positions_within(&mut self, elements: &[AstElement], plural_argument: Option<&str>) -> Result<(), String> {
+        for element in elements {
+            match element {
+                AstElement::Argument { value, .. } | AstElement::Date { value, .. } | AstElement::Time { value, .. }
+                    if plural_argument != Some(value.as_str()) =>
+                {
+                    self.position_for(value, "@")?;
+                }
+                AstElement::Number { value, style, .. } if plural_argument != Some(value.as_str()) => {
+                    self.position_for_number(value, style.as_ref().and_then(|style| serde_json::to_value(style).ok()))?;
+                }
+                AstElement::Plural { value, offset, options, .. } => {
+                    self.position_for(value, "lld")?;
+                    if *offset != 0 {
+                        self.argument_offsets.insert(value.clone(), *offset);
+                    }
+                    for (_, option) in &options.0 {
+                        self.assign_positions_within(&option.value, Some(value))?;
+                    }
+                }
+                AstElement::Select { value, options, .. } => {
+                    if self.device_argument.as_deref() != Some(value.as_str()) {
+                        self.position_for(value, "@")?;
+                    }
+                    for (_, option) in &options.0 {
+                        self.assign_positions_within(&option.value, plural_argument)?;
+                    }
+                }
+                AstElement::Tag { value, children, .. } => {
+                    if self.markdown_tags {
+                        self.markdown_delimiters(value)?;
+                    }
+                    self.assign_positions_within(children, plural_argument)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Switches the formatting mode, returning the previous one so callers can
+    /// restore it after formatting a plural's branches.
+    pub fn set_mode(&mut self, mode: FormatterMode) -> FormatterMode {
+        std::mem::replace(&mut self.formatter_mode, mode)
+    }
+
+    /// Sets what the plural's number becomes inside its branches, returning the
+    /// previous placeholder.
+    pub fn set_plural_placeholder(&mut self, placeholder: String) -> String {
+        std::mem::replace(&mut self.plural_placeholder, placeholder)
+    }
+
+    fn is_plural_argument(&self, value: &str) -> bool {
+        matches!(&self.formatter_mode, FormatterMode::Plural(argument) if argument == value)
+    }
+
+    pub fn argument_positions(&self) -> &HashMap<String, usize> {
+        &self.argument_positions
+    }
+
+    pub fn argument_specifiers(&self) -> &HashMap<String, String> {
+        &self.argument_specifiers
+    }
+
+    /// The format specifier (without `%`) recorded for an argument, e.g. `lld`.
+    pub fn specifier_for(&self, value: &str) -> Option<&str> {
+        self.argument_specifiers.get(value).map(String::as_str)
+    }
+
+    /// Describes the assigned placeholders for translators, ordered by position,
+    /// e.g. `%1$@ = name, %2$lld = count`. A plural with an offset receives the
+    /// offset value, shown as `%1$lld = count - 1`, and number styles a specifier
+    /// can't express add a hint, as in `%3$@ = price (pre-formatted USD amount)`.
+    /// Without `positioned` the entries match placeholders written without a
+    /// position, e.g. `%lld = count`.
+    pub fn placeholder_legend(&self, positioned: bool) -> Option<String> {
+        let mut positions: Vec<(&String, &usize)> = self.argument_positions.iter().collect();
+        positions.sort_by_key(|(_, position)| **position);
+
+        let entries: Vec<String> = positions
+            .into_iter()
+            .filter_map(|(name, position)| {
+                self.argument_specifiers
+                    .get(name)
+                    .map(|specifier| {
+                        let placeholder = if positioned {
+                            format!("%{}${}", position, specifier)
+                        } else {
+                            format!("%{}", specifier)
+                        };
+                        match (self.argument_offsets.get(name), self.argument_hints.get(name)) {
+                            (Some(offset), _) => format!("{} = {} - {}", placeholder, name, offset),
+                            (None, Some(hint)) => format!("{} = {} ({})", placeholder, name, hint),
+                            (None, None) => format!("{} = {}", placeholder, name),
+                        }
+                    })
+            })
+            .collect();
+
+        if entries.is_empty() {
+            None
+        } else {
+            Some(entries.join(", "))
+        }
+    }
+
+    /// Formats `elements` as one string unit or variation value. Literal `%` is
+    /// escaped as `%%` whenever the value is read as a format string: every string
+    /// of a key with arguments, since the app formats all its localizations with
+    /// them, and plural variations, which Foundation splices into the enclosing
+    /// format string.
+    pub fn format_elements(&mut self, elements: &[AstElement]) -> Result<String, String> {
+        let mut pieces = Vec::with_capacity(elements.len());
+        self.push_pieces(elements, &mut pieces)?;
+
+        let escape_percent =
+            matches!(self.formatter_mode, FormatterMode::Plural(_)) || !self.argument_positions.is_empty();
+
+        let mut result = String::with_capacity(pieces.iter().map(|(_, piece)| piece.len()).sum());
+        for (is_literal, piece) in pieces {
+            if is_literal && escape_percent {
+                result.push_str(&piece.replace('%', "%%"));
+            } else {
+                result.push_str(&piece);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Formats `elements` into `(is_literal, text)` pieces. Markdown tags are
+    /// flattened so the literals inside them are escaped like any other.
+    fn push_pieces(&mut self, elements: &[AstElement], pieces: &mut Vec<(bool, String)>) -> Result<(), String> {
+        for element in elements {
+            match element {
+                AstElement::Tag { value, children, .. } if self.markdown_tags => {
+                    let (open, close) = self.markdown_delimiters(value)?;
+                    pieces.push((false, open));
+                    self.push_pieces(children, pieces)?;
+                    pieces.push((false, close));
+                }
+                _ => pieces.push((matches!(element, AstElement::Literal { .. }), self.format(element)?)),
+            }
+        }
+        Ok(())
+    }
+
+    /// The Markdown around a tag's content: `**` for `<b>`, `*` for `<i>`, and
+    /// `[...](%N$@)` for `<link>`, whose URL the app passes as an argument named
+    /// after the tag.
+    fn markdown_delimiters(&mut self, tag: &str) -> Result<(String, String), String> {
+        match tag {
+            "b" => Ok(("**".to_string(), "**".to_string())),
+            "i" => Ok(("*".to_string(), "*".to_string())),
+            "link" => {
+                let position = self.position_for(tag, "@")?;
+                self.argument_hints.entry(tag.to_string()).or_insert_with(|| "URL".to_string());
+                Ok(("[".to_string(), format!("](%{}$@)", position)))
+            }
+            _ => Err(format!(
+                "Tag '<{}>' has no Markdown equivalent; only <b>, <i> and <link> are translated",
+                tag
+            )),
+        }
+    }
+
     pub fn format(&mut self, element: &AstElement) -> Result<String, String> {
         match &element {
             AstElement::Literal { value, .. } => Ok(value.clone()),
             AstElement::Argument { value, .. } => {
-                let position = self.get_or_insert_position(value)?;
-                match self.formatter_mode {
-                    FormatterMode::StringUnit => {
-                        let mut result = String::with_capacity(8);
-                        result.push('%');
-                        write!(result, "{}", position).unwrap();
-                        result.push_str("$@");
-                        Ok(result)
-                    }
-                    FormatterMode::Plural => Ok("%arg".to_string()),
+                if self.is_plural_argument(value) {
+                    return Ok(self.plural_placeholder.clone());
                 }
+                let position = self.position_for(value, "@")?;
+                let specifier = self.declared_specifiers.get(value).map_or("@", String::as_str);
+                let mut result = String::with_capacity(8);
+                result.push('%');
+                write!(result, "{}", position).unwrap();
+                result.push('$');
+                result.push_str(specifier);
+                Ok(result)
             }
-            AstElement::Number { value, .. } => {
-                let position = self.get_or_insert_position(value)?;
+            AstElement::Number { value, style, .. } => {
+                if self.is_plural_argument(value) {
+                    return Ok(self.plural_placeholder.clone());
+                }
+                let (position, specifier) =
+                    self.position_for_number(value, style.as_ref().and_then(|style| serde_json::to_value(style).ok()))?;
                 let mut result = String::with_capacity(10);
                 result.push('%');
                 write_number_to_string(&mut result, position);
-                result.push_str("$lld");
+                result.push('$');
+                result.push_str(&specifier);
                 Ok(result)
             }
-            AstElement::Date { value, .. } => {
-                let position = self.get_or_insert_position(value)?;
+            AstElement::Date { value, .. } | AstElement::Time { value, .. } => {
+                let position = self.position_for(value, "@")?;
                 let mut result = String::with_capacity(8);
                 result.push('%');
                 write_number_to_string(&mut result, position);
@@ -56,6 +308,7 @@ impl XCStringFormatter {
                 Ok(result)
             }
             AstElement::Plural { value, .. } => {
+                self.position_for(value, "lld")?;
                 let mut result = String::with_capacity(5 + value.len());
                 result.push_str("%#@");
                 result.push_str(value);
@@ -63,15 +316,56 @@ impl XCStringFormatter {
                 Ok(result)
             }
             AstElement::Select { value, .. } => {
+                // A String Catalog plural variation can't hold a select of its own.
+                if let FormatterMode::Plural(plural_argument) = &self.formatter_mode {
+                    return Err(format!(
+                        "Select '{}' inside plural '{}' can't be represented in a String Catalog",
+                        value, plural_argument
+                    ));
+                }
+                self.position_for(value, "@")?;
                 let mut result = String::with_capacity(5 + value.len());
                 result.push_str("%#@");
                 result.push_str(value);
                 result.push('@');
                 Ok(result)
             }
-            AstElement::Pound(_) => Ok("#".to_string()),
-            _ => Ok(String::new()),
+            AstElement::Pound(_) => match self.formatter_mode {
+                FormatterMode::StringUnit => Ok("#".to_string()),
+                FormatterMode::Plural(_) => Ok(self.plural_placeholder.clone()),
+            },
+            AstElement::Tag { .. } if self.markdown_tags => self.format_elements(std::slice::from_ref(element)),
+            AstElement::Tag { value, .. } => Err(format!(
+                "Tag '<{}>' can't be represented in a String Catalog; parse with ignore_tag to keep tags as text or enable Markdown tags",
+                value
+            )),
+        }
+    }
+
+    /// Returns the position of `value`, allocating the next one on first use and
+    /// recording `specifier` for it.
+    pub fn position_for(&mut self, value: &str, specifier: &str) -> Result<usize, String> {
+        let position = self.get_or_insert_position(value)?;
+        if !self.argument_specifiers.contains_key(value) {
+            self.argument_specifiers.insert(value.to_string(), specifier.to_string());
+        }
+        Ok(position)
+    }
+
+    /// Like `position_for`, with the specifier picked from the number's style
+    /// (serialized by the parser). Returns the specifier recorded for the
+    /// argument, which is the first style it was used with.
+    fn position_for_number(&mut self, value: &str, style: Option<serde_json::Value>) -> Result<(usize, String), String> {
+        let number_format = NumberFormat::from_style(style.as_ref());
+        let number_specifier = number_format.specifier();
+        let position = self.position_for(value, &number_specifier)?;
+        let specifier = self.specifier_for(value).unwrap_or("lld").to_string();
+        if specifier == number_specifier && !self.argument_hints.contains_key(value) {
+            if let Some(hint) = number_format.hint() {
+                self.argument_hints.insert(value.to_string(), hint);
+            }
         }
+        Ok((position, specifier))
     }
 
     fn get_or_insert_position(&mut self, value: &str) -> Result<usize, String> {
@@ -93,7 +387,7 @@ impl XCStringFormatter {
                 AstElement::Literal { value, .. } => value.len(),
                 AstElement::Argument { .. } => 4,
                 AstElement::Number { .. } => 6,
-                AstElement::Date { .. } => 4,
+                AstElement::Date { .. } | AstElement::Time { .. } => 4,
                 AstElement::Plural { value, .. } => 3 + value.len(),
                 AstElement::Select { value, .. } => 3 + value.len(),
                 AstElement::Pound(_) => 1,
@@ -130,7 +424,7 @@ impl XCStringFormatter {
                 AstElement::Literal { value, .. } => value.len(),
                 AstElement::Argument { .. } => 4, // "%1$@" の長さ
                 AstElement::Number { .. } => 6,   // "%1$lld" の長さ
-                AstElement::Date { .. } => 4,     // "%1$@" の長さ
+                AstElement::Date { .. } | AstElement::Time { .. } => 4, // "%1$@" の長さ
                 AstElement::Plural { value, .. } => 3 + value.len(), // "%#@...@" の長さ
                 AstElement::Select { value, .. } => 3 + value.len(), // "%#@...@" の長さ
                 AstElement::Pound(_) => 1,        // "#" の長さ
@@ -142,44 +436,7 @@ impl XCStringFormatter {
         
         // 一度に全ての要素を処理
         for element in elements {
-            match element {
-                AstElement::Literal { value, .. } => result.push_str(value),
-                AstElement::Argument { value, .. } => {
-                    let position = self.get_or_insert_position(value)?;
-                    match self.formatter_mode {
-                        FormatterMode::StringUnit => {
-                            result.push('%');
-                            write_number_to_string(&mut result, position);
-                            result.push_str("$@");
-                        }
-                        FormatterMode::Plural => result.push_str("%arg"),
-                    }
-                }
-                AstElement::Number { value, .. } => {
-                    let position = self.get_or_insert_position(value)?;
-                    result.push('%');
-                    write_number_to_string(&mut result, position);
-                    result.push_str("$lld");
-                }
-                AstElement::Date { value, .. } => {
-                    let position = self.get_or_insert_position(value)?;
-                    result.push('%');
-                    write_number_to_string(&mut result, position);
-                    result.push_str("$@");
-                }
-                AstElement::Plural { value, .. } => {
-                    result.push_str("%#@");
-                    result.push_str(value);
-                    result.push('@');
-                }
-                AstElement::Select { value, .. } => {
-                    result.push_str("%#@");
-                    result.push_str(value);
-                    result.push('@');
-                }
-                AstElement::Pound(_) => result.push('#'),
-                _ => {}
-            }
+            result.push_str(&self.format(element)?);
         }
         
         Ok(result)
@@ -240,6 +497,99 @@ mod test {
         assert_eq!(formatter.format_batch(&elements).unwrap(), "Hello, %1$@!");
     }
 
+    #[test]
+    fn test_format_plural_branch() {
+        let mut formatter = super::XCStringFormatter::new(super::FormatterMode::Plural("count".to_string()));
+        let pound = AstElement::Pound(icu_messageformat_parser::Span::new(
+            icu_messageformat_parser::Position::new(0, 1, 1),
+            icu_messageformat_parser::Position::new(1, 1, 2),
+        ));
+        assert_eq!(formatter.format(&pound).unwrap(), "%arg");
+        let element = AstElement::Argument {
+            value: "count".to_string(),
+            span: None,
+        };
+        assert_eq!(formatter.format(&element).unwrap(), "%arg");
+        let element = AstElement::Argument {
+            value: "owner".to_string(),
+            span: None,
+        };
+        assert_eq!(formatter.format(&element).unwrap(), "%1$@");
+    }
+
+    #[test]
+    fn test_format_elements_escapes_percent() {
+        let literal = |value: &str| AstElement::Literal {
+            value: value.to_string(),
+            span: None,
+        };
+        let item = AstElement::Argument {
+            value: "item".to_string(),
+            span: None,
+        };
+
+        let mut formatter = super::XCStringFormatter::new(super::FormatterMode::StringUnit);
+        assert_eq!(formatter.format_elements(&[literal("50% off")]).unwrap(), "50% off");
+        assert_eq!(
+            formatter.format_elements(&[literal("50% off "), item.clone()]).unwrap(),
+            "50%% off %1$@"
+        );
+
+        let mut formatter = super::XCStringFormatter::new(super::FormatterMode::Plural("count".to_string()));
+        assert_eq!(formatter.format_elements(&[literal("100% done")]).unwrap(), "100%% done");
+
+        // 他のロケールに引数があるキーでは、引数のない文字列もエスケープする
+        let positions = std::collections::HashMap::from([("count".to_string(), 1)]);
+        let mut formatter =
+            super::XCStringFormatter::with_positions(super::FormatterMode::StringUnit, positions, Default::default());
+        assert_eq!(formatter.format_elements(&[literal("100% done")]).unwrap(), "100%% done");
+    }
+
+    #[test]
+    fn test_with_positions_keeps_source_specifiers() {
+        let options = icu_messageformat_parser::ParserOptions::default();
+        let mut parser = icu_messageformat_parser::Parser::new("{ratio, number, percent} done", &options);
+        let source = parser.parse().unwrap();
+        let mut source_formatter = super::XCStringFormatter::new(super::FormatterMode::StringUnit);
+        source_formatter.assign_positions(&source).unwrap();
+
+        // 翻訳側がスタイルを省略しても、ソースと同じ指定子を使う
+        let mut parser = icu_messageformat_parser::Parser::new("{ratio, number} 完了", &options);
+        let translation = parser.parse().unwrap();
+        let mut formatter = super::XCStringFormatter::with_positions(
+            super::FormatterMode::StringUnit,
+            source_formatter.argument_positions().clone(),
+            source_formatter.argument_specifiers().clone(),
+        );
+        assert_eq!(formatter.format_elements(&translation).unwrap(), "%1$@ 完了");
+    }
+
+    #[test]
+    fn test_placeholder_legend() {
+        let mut formatter = super::XCStringFormatter::new(super::FormatterMode::StringUnit);
+        assert_eq!(formatter.placeholder_legend(true), None);
+        let elements = vec![
+            AstElement::Argument {
+                value: "name".to_string(),
+                span: None,
+            },
+            AstElement::Number {
+                value: "count".to_string(),
+                style: None,
+                span: None,
+            },
+        ];
+        formatter.assign_positions(&elements).unwrap();
+        assert_eq!(
+            formatter.placeholder_legend(true).unwrap(),
+            "%1$@ = name, %2$lld = count"
+        );
+        assert_eq!(
+            formatter.placeholder_legend(false).unwrap(),
+            "%@ = name, %lld = count"
+        );
+    }
+
     #[test]
     fn test_deserialize_localizable_icu_message() {
         let json = r#"{
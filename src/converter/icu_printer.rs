@@ -0,0 +1,213 @@
+//! Serializes a parsed ICU message back to ICU MessageFormat text.
+//!
+//! Used when the converter rewrites a message on the AST (e.g. select
+//! splitting) and the result has to go through the regular conversion again.
+
+use crate::converter::validation;
+use icu_messageformat_parser::AstElement;
+
+/// Prints `elements` as ICU MessageFormat text that parses back to the same AST.
+pub fn print(elements: &[AstElement]) -> String {
+    let mut result = String::new();
+    print_elements(elements, false, &mut result);
+    result
+}
+
+fn print_elements(elements: &[AstElement], in_plural: bool, result: &mut String) {
+    for element in elements {
+        print_element(element, in_plural, result);
+    }
+}
+
+fn print_element(element: &AstElement, in_plural: bool, result: &mut String) {
+    match element {
+        AstElement::Literal { value, .. } => print_literal(value, in_plural, result),
+        AstElement::Argument { value, .. } => {
+            result.push('{');
+            result.push_str(value);
+            result.push('}');
+        }
+        AstElement::Number { value, style, .. } => {
+            print_formatted_argument(value, "number", style.as_ref().map(serde_json::to_value), result)
+        }
+        AstElement::Date { value, style, .. } => {
+            print_formatted_argument(value, "date", style.as_ref().map(serde_json::to_value), result)
+        }
+        AstElement::Time { value, style, .. } => {
+            print_formatted_argument(value, "time", style.as_ref().map(serde_json::to_value), result)
+        }
+        AstElement::Select { value, options, .. } => {
+            result.push('{');
+            result.push_str(value);
+            result.push_str(", select,");
+            for (selector, option) in &options.0 {
+                result.push(' ');
+                result.push_str(selector);
+                result.push_str(" {");
+                print_elements(&option.value, false, result);
+                result.push('}');
+            }
+            result.push('}');
+        }
+        AstElement::Plural { value, plural_type, offset, options, .. } => {
+            result.push('{');
+            result.push_str(value);
+            if validation::is_ordinal(plural_type) {
+                result.push_str(", selectordinal,");
+            } else {
+                result.push_str(", plural,");
+            }
+            if *offset != 0 {
+                result.push_str(&format!(" offset:{}", offset));
+            }
+            for (selector, option) in &options.0 {
+                result.push(' ');
+                result.push_str(selector);
+                result.push_str(" {");
+                print_elements(&option.value, true, result);
+                result.push('}');
+            }
+            result.push('}');
+        }
+        AstElement::Pound(_) => result.push('#'),
+        AstElement::Tag { value, children, .. } => {
+            result.push('<');
+            result.push_str(value);
+            result.push('>');
+            print_elements(children, in_plural, result);
+            result.push_str("</");
+            result.push_str(value);
+            result.push('>');
+        }
+    }
+}
+
+/// Styles are only reachable through serde, since the parser doesn't export
+/// their types: a plain style serializes to a string, a skeleton to an object.
+fn print_formatted_argument(
+    value: &str,
+    argument_type: &str,
+    style: Option<serde_json::Result<serde_json::Value>>,
+    result: &mut String,
+) {
+    result.push('{');
+    result.push_str(value);
+    result.push_str(", ");
+    result.push_str(argument_type);
+    match style {
+        Some(Ok(serde_json::Value::String(style))) => {
+            result.push_str(", ");
+            result.push_str(&style);
+        }
+        Some(Ok(skeleton)) => {
+            if let Some(pattern) = skeleton["pattern"].as_str() {
+                result.push_str(", ::");
+                result.push_str(pattern);
+            } else if let Some(tokens) = skeleton["tokens"].as_array() {
+                let tokens: Vec<String> = tokens
+                    .iter()
+                    .map(|token| {
+                        let mut stem = token["stem"].as_str().unwrap_or("").to_string();
+                        for option in token["options"].as_array().into_iter().flatten() {
+                            stem.push('/');
+                            stem.push_str(option.as_str().unwrap_or(""));
+                        }
+                        stem
+                    })
+                    .collect();
+                result.push_str(", ::");
+                result.push_str(&tokens.join(" "));
+            }
+        }
+        _ => {}
+    }
+    result.push('}');
+}
+
+/// Quotes the characters ICU treats as syntax. Consecutive special characters
+/// share one quoted run, and apostrophes are always doubled.
+fn print_literal(value: &str, in_plural: bool, result: &mut String) {
+    let mut quoted = false;
+    for c in value.chars() {
+        let special = matches!(c, '{' | '}' | '<') || (in_plural && c == '#');
+        if special {
+            if !quoted {
+                result.push('\'');
+                quoted = true;
+            }
+            result.push(c);
+        } else if c == '\'' {
+            result.push_str("''");
+        } else {
+            if quoted {
+                result.push('\'');
+                quoted = false;
+            }
+            result.push(c);
+        }
+    }
+    if quoted {
+        result.push('\'');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::print;
+    use icu_messageformat_parser::{Parser, ParserOptions};
+
+    // `#` always carries its location, which shifts when quoting changes.
+    fn without_locations(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => map
+                .into_iter()
+                .filter(|(key, _)| key != "location")
+                .map(|(key, value)| (key, without_locations(value)))
+                .collect(),
+            serde_json::Value::Array(values) => values.into_iter().map(without_locations).collect(),
+            value => value,
+        }
+    }
+
+    fn assert_round_trip(message: &str, options: &ParserOptions) {
+        let mut parser = Parser::new(message, options);
+        let parsed = parser.parse().unwrap();
+        let printed = print(&parsed);
+        let mut parser = Parser::new(&printed, options);
+        let reparsed = parser.parse().unwrap();
+        assert_eq!(
+            without_locations(serde_json::to_value(&parsed).unwrap()),
+            without_locations(serde_json::to_value(&reparsed).unwrap()),
+            "printed as {}",
+            printed
+        );
+    }
+
+    #[test]
+    fn test_print() {
+        let options = ParserOptions::default();
+        let mut parser = Parser::new(
+            "{name} has {count, plural, offset:1 =0 {none} one {# item} other {# items}} on {day, date, short}",
+            &options,
+        );
+        let parsed = parser.parse().unwrap();
+        assert_eq!(
+            print(&parsed),
+            "{name} has {count, plural, offset:1 =0 {none} one {# item} other {# items}} on {day, date, short}"
+        );
+    }
+
+    #[test]
+    fn test_print_round_trip() {
+        let options = ParserOptions::default();
+        assert_round_trip("It''s '{'braces'}' and '{}' and '<'b>", &options);
+        assert_round_trip("{count, plural, other {'#' is # and it''s {gender, select, other {#}}}}", &options);
+        assert_round_trip("{place, selectordinal, one {#st} two {#nd} other {#th}}", &options);
+        assert_round_trip("{price, number, ::currency/USD precision-integer} {ratio, number, percent}", &options);
+        assert_round_trip("{when, time, ::hhmm} {day, date}", &options);
+        assert_round_trip("Click <link>here</link>", &options);
+
+        let ignore_tag = ParserOptions { ignore_tag: true, ..ParserOptions::default() };
+        assert_round_trip("Click <b>here</b> for {count, plural, other {#}}", &ignore_tag);
+    }
+}
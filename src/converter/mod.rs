@@ -1,6 +1,10 @@
 pub mod xcstring_converter;
 pub mod formatter;
 pub mod substitution_builder;
+pub mod plural_rules;
+pub mod validation;
+pub mod icu_printer;
+pub mod number_format;
 
 // Re-export the main converter for convenience
 pub use xcstring_converter::XCStringConverter; 
\ No newline at end of file
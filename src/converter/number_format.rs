@@ -0,0 +1,234 @@
+//! Reads the style of a `{x, number, ...}` argument into a small IR and picks
+//! the printf specifier a String Catalog can use for it.
+//!
+//! Skeletons are read from their tokens rather than from the parser's
+//! `should_parse_skeletons` output, which panics on some valid skeletons
+//! (e.g. `::integer-width/+00`) and leaves older forms like `::.00+` unparsed.
+
+/// How a number argument is meant to be displayed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NumberFormat {
+    pub kind: NumberKind,
+    pub precision: Precision,
+    /// `compact-short`, `scientific`, ...
+    pub notation: Option<String>,
+    pub scale: Option<String>,
+    /// Style parts with no printf equivalent that don't already make the value
+    /// pre-formatted, e.g. `sign-always`.
+    pub unsupported: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum NumberKind {
+    #[default]
+    Plain,
+    Percent,
+    Currency(Option<String>),
+    Unit(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Precision {
+    /// No precision given, which the converter has always treated as an integer.
+    #[default]
+    Default,
+    Integer,
+    /// Fraction digits as in `.00#`; `max` is `None` for `.00+` and
+    /// `precision-unlimited`.
+    Fraction { min: u32, max: Option<u32> },
+}
+
+impl NumberFormat {
+    /// Reads a number style as serialized by the parser: a plain style is a
+    /// string, a skeleton an object with `tokens`.
+    pub fn from_style(style: Option<&serde_json::Value>) -> NumberFormat {
+        let mut format = NumberFormat::default();
+        match style {
+            None => {}
+            Some(serde_json::Value::String(style)) => match style.as_str() {
+                "integer" => format.precision = Precision::Integer,
+                "percent" => format.kind = NumberKind::Percent,
+                "currency" => format.kind = NumberKind::Currency(None),
+                other => format.unsupported.push(other.to_string()),
+            },
+            Some(skeleton) => {
+                for token in skeleton["tokens"].as_array().into_iter().flatten() {
+                    let stem = token["stem"].as_str().unwrap_or("");
+                    let options: Vec<&str> = token["options"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|option| option.as_str())
+                        .collect();
+                    format.apply_token(stem, &options);
+                }
+            }
+        }
+        format
+    }
+
+    fn apply_token(&mut self, stem: &str, options: &[&str]) {
+        match stem {
+            "precision-integer" => self.precision = Precision::Integer,
+            "precision-unlimited" => self.precision = Precision::Fraction { min: 0, max: None },
+            "percent" | "%" => self.kind = NumberKind::Percent,
+            "currency" if !options.is_empty() => self.kind = NumberKind::Currency(Some(options[0].to_string())),
+            "measure-unit" | "unit" if !options.is_empty() => self.kind = NumberKind::Unit(options[0].to_string()),
+            "compact-short" | "K" | "compact-long" | "KK" | "scientific" | "engineering" => {
+                self.notation = Some(stem.to_string())
+            }
+            "scale" if !options.is_empty() => self.scale = Some(options[0].to_string()),
+            // `%lld` and `%f` never group, so grouping has nothing to map to.
+            "group-off" | ",_" | "group-min2" | ",?" | "group-auto" | "group-on-aligned" | ",!"
+            | "group-thousands" | ",=" => {}
+            _ => match fraction_precision(stem) {
+                Some(precision) if options.is_empty() => self.precision = precision,
+                _ => self.unsupported.push(token_text(stem, options)),
+            },
+        }
+    }
+
+    /// The specifier (without `%`) for the argument, e.g. `lld`, `.2f` or `@`
+    /// when the app has to pass a pre-formatted string.
+    pub fn specifier(&self) -> String {
+        if self.is_pre_formatted() {
+            return "@".to_string();
+        }
+        match self.precision {
+            Precision::Default | Precision::Integer => "lld".to_string(),
+            Precision::Fraction { max: Some(0), .. } => "lld".to_string(),
+            Precision::Fraction { max: Some(max), .. } => format!(".{}f", max),
+            Precision::Fraction { max: None, .. } => "f".to_string(),
+        }
+    }
+
+    /// A note for translators when the specifier doesn't carry the whole
+    /// formatting intent, e.g. `pre-formatted USD amount`.
+    pub fn hint(&self) -> Option<String> {
+        let mut notes = Vec::new();
+        if self.is_pre_formatted() {
+            let mut description = match &self.kind {
+                NumberKind::Plain => "pre-formatted number".to_string(),
+                NumberKind::Percent => "pre-formatted percentage".to_string(),
+                NumberKind::Currency(Some(currency)) => format!("pre-formatted {} amount", currency),
+                NumberKind::Currency(None) => "pre-formatted currency amount".to_string(),
+                NumberKind::Unit(unit) => format!("pre-formatted {} value", unit),
+            };
+            if let Some(notation) = &self.notation {
+                description.push_str(&format!(" in {} notation", notation));
+            }
+            if let Some(scale) = &self.scale {
+                description.push_str(&format!(", scaled by {}", scale));
+            }
+            notes.push(description);
+        } else {
+            match self.precision {
+                Precision::Fraction { min, max: Some(max) } if min < max && max > 0 => {
+                    notes.push(format!("always shows {} decimals, ICU drops trailing zeros down to {}", max, min))
+                }
+                Precision::Fraction { min, max: None } => {
+                    notes.push(format!("always shows 6 decimals, ICU shows at least {}", min))
+                }
+                _ => {}
+            }
+        }
+        if !self.unsupported.is_empty() {
+            notes.push(format!("'{}' isn't applied", self.unsupported.join(" ")));
+        }
+
+        if notes.is_empty() {
+            None
+        } else {
+            Some(notes.join("; "))
+        }
+    }
+
+    fn is_pre_formatted(&self) -> bool {
+        self.kind != NumberKind::Plain || self.notation.is_some() || self.scale.is_some()
+    }
+}
+
+/// Reads a fraction precision stem: `.00` (exactly two), `.0#` (one or two),
+/// `.##` (up to two) or `.00+` / `.00*` (at least two).
+fn fraction_precision(stem: &str) -> Option<Precision> {
+    let digits = stem.strip_prefix('.')?;
+    let (digits, unbounded) = match digits.strip_suffix(['+', '*']) {
+        Some(digits) => (digits, true),
+        None => (digits, false),
+    };
+    let min = digits.chars().take_while(|&c| c == '0').count();
+    let optional = digits[min..].chars().take_while(|&c| c == '#').count();
+    if min + optional != digits.len() || (unbounded && optional > 0) {
+        return None;
+    }
+    let max = if unbounded { None } else { Some((min + optional) as u32) };
+    Some(Precision::Fraction { min: min as u32, max })
+}
+
+fn token_text(stem: &str, options: &[&str]) -> String {
+    let mut text = stem.to_string();
+    for option in options {
+        text.push('/');
+        text.push_str(option);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NumberFormat;
+    use icu_messageformat_parser::{AstElement, Parser, ParserOptions};
+
+    fn number_format(message: &str) -> NumberFormat {
+        let options = ParserOptions::default();
+        let mut parser = Parser::new(message, &options);
+        let parsed = parser.parse().unwrap();
+        match &parsed[0] {
+            AstElement::Number { style, .. } => {
+                let style = style.as_ref().map(|style| serde_json::to_value(style).unwrap());
+                NumberFormat::from_style(style.as_ref())
+            }
+            element => panic!("not a number argument: {:?}", element),
+        }
+    }
+
+    #[test]
+    fn test_specifier() {
+        assert_eq!(number_format("{n, number}").specifier(), "lld");
+        assert_eq!(number_format("{n, number, integer}").specifier(), "lld");
+        assert_eq!(number_format("{n, number, ::precision-integer}").specifier(), "lld");
+        assert_eq!(number_format("{n, number, ::.00}").specifier(), ".2f");
+        assert_eq!(number_format("{n, number, ::.0## group-off}").specifier(), ".3f");
+        assert_eq!(number_format("{n, number, ::.0+}").specifier(), "f");
+        assert_eq!(number_format("{n, number, percent}").specifier(), "@");
+        assert_eq!(number_format("{n, number, ::currency/USD .00}").specifier(), "@");
+        assert_eq!(number_format("{n, number, ::measure-unit/length-kilometer}").specifier(), "@");
+        assert_eq!(number_format("{n, number, ::compact-short}").specifier(), "@");
+    }
+
+    #[test]
+    fn test_hint() {
+        assert_eq!(number_format("{n, number}").hint(), None);
+        assert_eq!(number_format("{n, number, ::.00}").hint(), None);
+        assert_eq!(
+            number_format("{n, number, ::currency/USD}").hint().as_deref(),
+            Some("pre-formatted USD amount")
+        );
+        assert_eq!(
+            number_format("{n, number, percent}").hint().as_deref(),
+            Some("pre-formatted percentage")
+        );
+        assert_eq!(
+            number_format("{n, number, ::compact-short scale/1000}").hint().as_deref(),
+            Some("pre-formatted number in compact-short notation, scaled by 1000")
+        );
+        assert_eq!(
+            number_format("{n, number, ::.0#}").hint().as_deref(),
+            Some("always shows 2 decimals, ICU drops trailing zeros down to 1")
+        );
+        assert_eq!(
+            number_format("{n, number, ::sign-always integer-width/+00}").hint().as_deref(),
+            Some("'sign-always integer-width/+00' isn't applied")
+        );
+    }
+}
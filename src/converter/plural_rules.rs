@@ -0,0 +1,539 @@
+//! Embedded CLDR plural rules.
+//!
+//! Only the integer part of each rule is evaluated, since String Catalog plural
+//! arguments are integers (`%lld`). The category sets still follow CLDR so that
+//! categories used only for decimals are reported as required.
+//!
+//! Ordinal rules cover every language with embedded cardinal rules; the
+//! languages whose ordinals use only `other` share a single family.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn from_keyword(keyword: &str) -> Option<PluralCategory> {
+        match keyword {
+            "zero" => Some(PluralCategory::Zero),
+            "one" => Some(PluralCategory::One),
+            "two" => Some(PluralCategory::Two),
+            "few" => Some(PluralCategory::Few),
+            "many" => Some(PluralCategory::Many),
+            "other" => Some(PluralCategory::Other),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+use PluralCategory::{Few, Many, One, Other, Two, Zero};
+
+/// CLDR cardinal rule families, grouped by languages that share a rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CardinalRule {
+    OtherOnly,
+    /// one: n = 1
+    One,
+    /// one: i = 0,1
+    ZeroOrOne,
+    /// one: n = 1; many: multiples of 1,000,000
+    OneMillions,
+    /// one: i = 0,1; many: multiples of 1,000,000
+    ZeroOrOneMillions,
+    /// one: i % 10 = 1 and i % 100 != 11
+    OneEndingInOne,
+    Filipino,
+    EastSlavic,
+    Polish,
+    Czech,
+    Lithuanian,
+    Latvian,
+    SerboCroatian,
+    Romanian,
+    Slovenian,
+    Hebrew,
+    Irish,
+    ScottishGaelic,
+    Maltese,
+    Arabic,
+    Welsh,
+}
+
+fn cardinal_rule(locale: &str) -> Option<CardinalRule> {
+    let normalized = locale.replace('_', "-").to_lowercase();
+    if normalized == "pt-pt" {
+        return Some(CardinalRule::OneMillions);
+    }
+    let language = normalized.split('-').next().unwrap_or("");
+    let rule = match language {
+        "bo" | "dz" | "id" | "ig" | "ii" | "ja" | "jv" | "kea" | "km" | "ko" | "lo" | "ms"
+        | "my" | "sah" | "sg" | "su" | "th" | "to" | "vi" | "wo" | "yo" | "yue" | "zh" => {
+            CardinalRule::OtherOnly
+        }
+        "af" | "az" | "bg" | "da" | "de" | "el" | "en" | "et" | "eu" | "fi" | "fy" | "gl"
+        | "ha" | "hu" | "ka" | "kk" | "ky" | "lb" | "ml" | "mn" | "mr" | "nb" | "ne" | "nl"
+        | "nn" | "no" | "or" | "ps" | "sq" | "sv" | "sw" | "ta" | "te" | "tk" | "tr" | "ug"
+        | "ur" | "uz" => CardinalRule::One,
+        "am" | "as" | "bn" | "fa" | "ff" | "gu" | "hi" | "hy" | "kab" | "kn" | "si" | "zu" => {
+            CardinalRule::ZeroOrOne
+        }
+        "ca" | "es" | "it" => CardinalRule::OneMillions,
+        "fr" | "pt" => CardinalRule::ZeroOrOneMillions,
+        "is" | "mk" => CardinalRule::OneEndingInOne,
+        "fil" | "tl" => CardinalRule::Filipino,
+        "be" | "ru" | "uk" => CardinalRule::EastSlavic,
+        "pl" => CardinalRule::Polish,
+        "cs" | "sk" => CardinalRule::Czech,
+        "lt" => CardinalRule::Lithuanian,
+        "lv" => CardinalRule::Latvian,
+        "bs" | "hr" | "sh" | "sr" => CardinalRule::SerboCroatian,
+        "mo" | "ro" => CardinalRule::Romanian,
+        "sl" => CardinalRule::Slovenian,
+        "he" | "iw" => CardinalRule::Hebrew,
+        "ga" => CardinalRule::Irish,
+        "gd" => CardinalRule::ScottishGaelic,
+        "mt" => CardinalRule::Maltese,
+        "ar" | "ars" => CardinalRule::Arabic,
+        "cy" => CardinalRule::Welsh,
+        _ => return None,
+    };
+    Some(rule)
+}
+
+/// The cardinal plural categories CLDR defines for `locale`, or `None` when the
+/// locale has no embedded rules.
+pub fn cardinal_categories(locale: &str) -> Option<&'static [PluralCategory]> {
+    let categories: &'static [PluralCategory] = match cardinal_rule(locale)? {
+        CardinalRule::OtherOnly => &[Other],
+        CardinalRule::One
+        | CardinalRule::ZeroOrOne
+        | CardinalRule::OneEndingInOne
+        | CardinalRule::Filipino => &[One, Other],
+        CardinalRule::OneMillions | CardinalRule::ZeroOrOneMillions => &[One, Many, Other],
+        CardinalRule::EastSlavic | CardinalRule::Polish | CardinalRule::Czech | CardinalRule::Lithuanian => {
+            &[One, Few, Many, Other]
+        }
+        CardinalRule::Latvian => &[Zero, One, Other],
+        CardinalRule::SerboCroatian | CardinalRule::Romanian => &[One, Few, Other],
+        CardinalRule::Slovenian | CardinalRule::ScottishGaelic => &[One, Two, Few, Other],
+        CardinalRule::Hebrew => &[One, Two, Other],
+        CardinalRule::Irish | CardinalRule::Maltese => &[One, Two, Few, Many, Other],
+        CardinalRule::Arabic | CardinalRule::Welsh => &[Zero, One, Two, Few, Many, Other],
+    };
+    Some(categories)
+}
+
+/// The cardinal category CLDR selects for the integer `n` in `locale`.
+pub fn cardinal_category(locale: &str, n: u64) -> Option<PluralCategory> {
+    let i10 = n % 10;
+    let i100 = n % 100;
+    let category = match cardinal_rule(locale)? {
+        CardinalRule::OtherOnly => Other,
+        CardinalRule::One => match n {
+            1 => One,
+            _ => Other,
+        },
+        CardinalRule::ZeroOrOne => match n {
+            0 | 1 => One,
+            _ => Other,
+        },
+        CardinalRule::OneMillions => match n {
+            1 => One,
+            _ if n.is_multiple_of(1_000_000) && n != 0 => Many,
+            _ => Other,
+        },
+        CardinalRule::ZeroOrOneMillions => match n {
+            0 | 1 => One,
+            _ if n.is_multiple_of(1_000_000) => Many,
+            _ => Other,
+        },
+        CardinalRule::OneEndingInOne => match (i10, i100) {
+            (1, i100) if i100 != 11 => One,
+            _ => Other,
+        },
+        CardinalRule::Filipino => match (n, i10) {
+            (1..=3, _) => One,
+            (_, 4 | 6 | 9) => Other,
+            _ => One,
+        },
+        CardinalRule::EastSlavic => match (i10, i100) {
+            (1, i100) if i100 != 11 => One,
+            (2..=4, i100) if !(12..=14).contains(&i100) => Few,
+            _ => Many,
+        },
+        CardinalRule::Polish => match (n, i10, i100) {
+            (1, _, _) => One,
+            (_, 2..=4, i100) if !(12..=14).contains(&i100) => Few,
+            _ => Many,
+        },
+        CardinalRule::Czech => match n {
+            1 => One,
+            2..=4 => Few,
+            _ => Other,
+        },
+        CardinalRule::Lithuanian => match (i10, i100) {
+            (_, 11..=19) => Other,
+            (1, _) => One,
+            (2..=9, _) => Few,
+            _ => Other,
+        },
+        CardinalRule::Latvian => match (i10, i100) {
+            (0, _) | (_, 11..=19) => Zero,
+            (1, _) => One,
+            _ => Other,
+        },
+        CardinalRule::SerboCroatian => match (i10, i100) {
+            (1, i100) if i100 != 11 => One,
+            (2..=4, i100) if !(12..=14).contains(&i100) => Few,
+            _ => Other,
+        },
+        CardinalRule::Romanian => match (n, i100) {
+            (1, _) => One,
+            (0, _) | (_, 1..=19) => Few,
+            _ => Other,
+        },
+        CardinalRule::Slovenian => match i100 {
+            1 => One,
+            2 => Two,
+            3 | 4 => Few,
+            _ => Other,
+        },
+        CardinalRule::Hebrew => match n {
+            1 => One,
+            2 => Two,
+            _ => Other,
+        },
+        CardinalRule::Irish => match n {
+            1 => One,
+            2 => Two,
+            3..=6 => Few,
+            7..=10 => Many,
+            _ => Other,
+        },
+        CardinalRule::ScottishGaelic => match n {
+            1 | 11 => One,
+            2 | 12 => Two,
+            3..=10 | 13..=19 => Few,
+            _ => Other,
+        },
+        CardinalRule::Maltese => match (n, i100) {
+            (1, _) => One,
+            (2, _) => Two,
+            (0, _) | (_, 3..=10) => Few,
+            (_, 11..=19) => Many,
+            _ => Other,
+        },
+        CardinalRule::Arabic => match (n, i100) {
+            (0, _) => Zero,
+            (1, _) => One,
+            (2, _) => Two,
+            (_, 3..=10) => Few,
+            (_, 11..=99) => Many,
+            _ => Other,
+        },
+        CardinalRule::Welsh => match n {
+            0 => Zero,
+            1 => One,
+            2 => Two,
+            3 => Few,
+            6 => Many,
+            _ => Other,
+        },
+    };
+    Some(category)
+}
+
+/// CLDR ordinal rule families.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OrdinalRule {
+    OtherOnly,
+    /// one: n = 1
+    One,
+    /// one: n % 10 = 1; two: n % 10 = 2; few: n % 10 = 3; excluding 11-13
+    English,
+    /// one: n % 10 = 1,2 and n % 100 != 11,12
+    Swedish,
+    /// one: n = 1,5
+    Hungarian,
+    /// one: n = 1,3; two: n = 2; few: n = 4
+    Catalan,
+    /// many: n = 11,8,80,800
+    Italian,
+    /// one: n = 1; two: n = 2,3; few: n = 4; many: n = 6
+    Hindi,
+    /// one: n = 1,5,7-10; two: n = 2,3; few: n = 4; many: n = 6
+    Bengali,
+    /// one: n = 1; two: n = 2,3; few: n = 4
+    Marathi,
+    /// one: n = 1-4
+    Nepali,
+    /// few: n % 10 = 3 and n % 100 != 13
+    Ukrainian,
+    /// few: n % 10 = 2,3 and n % 100 != 12,13
+    Belarusian,
+    /// many: n % 10 = 6,9 or n % 10 = 0 and n != 0
+    Kazakh,
+    /// few: n % 10 = 6,9 or n = 10
+    Turkmen,
+    /// one: n = 1; many: n % 10 = 4 and n % 100 != 14
+    Albanian,
+    /// one: n = 1; many: n = 0 or n % 100 = 2-20,40,60,80
+    Georgian,
+    /// one: n % 10 = 1; two: n % 10 = 2; many: n % 10 = 7,8; excluding 11-19
+    Macedonian,
+    /// one: n % 10 = 1,2,5,7,8 or n % 100 = 20,50,70,80; few: n % 10 = 3,4 or
+    /// n % 1000 = 100-900; many: n = 0 or n % 10 = 6 or n % 100 = 40,60,90
+    Azerbaijani,
+    /// one: n = 1,11; two: n = 2,12; few: n = 3,13
+    ScottishGaelic,
+    Welsh,
+}
+
+fn ordinal_rule(locale: &str) -> Option<OrdinalRule> {
+    let normalized = locale.replace('_', "-").to_lowercase();
+    let language = normalized.split('-').next().unwrap_or("");
+    let rule = match language {
+        "en" => OrdinalRule::English,
+        "fr" | "fil" | "tl" | "ga" | "hy" | "lo" | "mo" | "ms" | "ro" | "vi" => OrdinalRule::One,
+        "sv" => OrdinalRule::Swedish,
+        "hu" => OrdinalRule::Hungarian,
+        "ca" => OrdinalRule::Catalan,
+        "it" | "sc" | "scn" => OrdinalRule::Italian,
+        "gu" | "hi" => OrdinalRule::Hindi,
+        "as" | "bn" | "or" => OrdinalRule::Bengali,
+        "mr" => OrdinalRule::Marathi,
+        "ne" => OrdinalRule::Nepali,
+        "uk" => OrdinalRule::Ukrainian,
+        "be" => OrdinalRule::Belarusian,
+        "kk" => OrdinalRule::Kazakh,
+        "tk" => OrdinalRule::Turkmen,
+        "sq" => OrdinalRule::Albanian,
+        "ka" => OrdinalRule::Georgian,
+        "mk" => OrdinalRule::Macedonian,
+        "az" => OrdinalRule::Azerbaijani,
+        "gd" => OrdinalRule::ScottishGaelic,
+        "cy" => OrdinalRule::Welsh,
+        _ => {
+            cardinal_rule(locale)?;
+            OrdinalRule::OtherOnly
+        }
+    };
+    Some(rule)
+}
+
+/// The ordinal (`selectordinal`) categories CLDR defines for `locale`, or `None`
+/// when the locale has no embedded rules.
+pub fn ordinal_categories(locale: &str) -> Option<&'static [PluralCategory]> {
+    let categories: &'static [PluralCategory] = match ordinal_rule(locale)? {
+        OrdinalRule::OtherOnly => &[Other],
+        OrdinalRule::One | OrdinalRule::Swedish | OrdinalRule::Hungarian | OrdinalRule::Nepali => {
+            &[One, Other]
+        }
+        OrdinalRule::English
+        | OrdinalRule::Catalan
+        | OrdinalRule::Marathi
+        | OrdinalRule::ScottishGaelic => &[One, Two, Few, Other],
+        OrdinalRule::Italian | OrdinalRule::Kazakh => &[Many, Other],
+        OrdinalRule::Ukrainian | OrdinalRule::Belarusian | OrdinalRule::Turkmen => &[Few, Other],
+        OrdinalRule::Albanian | OrdinalRule::Georgian => &[One, Many, Other],
+        OrdinalRule::Macedonian => &[One, Two, Many, Other],
+        OrdinalRule::Azerbaijani => &[One, Few, Many, Other],
+        OrdinalRule::Hindi | OrdinalRule::Bengali => &[One, Two, Few, Many, Other],
+        OrdinalRule::Welsh => &[Zero, One, Two, Few, Many, Other],
+    };
+    Some(categories)
+}
+
+/// The ordinal category CLDR selects for the integer `n` in `locale`.
+pub fn ordinal_category(locale: &str, n: u64) -> Option<PluralCategory> {
+    let i10 = n % 10;
+    let i100 = n % 100;
+    let category = match ordinal_rule(locale)? {
+        OrdinalRule::OtherOnly => Other,
+        OrdinalRule::One => match n {
+            1 => One,
+            _ => Other,
+        },
+        OrdinalRule::English => match (i10, i100) {
+            (1, i100) if i100 != 11 => One,
+            (2, i100) if i100 != 12 => Two,
+            (3, i100) if i100 != 13 => Few,
+            _ => Other,
+        },
+        OrdinalRule::Swedish => match (i10, i100) {
+            (1 | 2, i100) if i100 != 11 && i100 != 12 => One,
+            _ => Other,
+        },
+        OrdinalRule::Hungarian => match n {
+            1 | 5 => One,
+            _ => Other,
+        },
+        OrdinalRule::Catalan => match n {
+            1 | 3 => One,
+            2 => Two,
+            4 => Few,
+            _ => Other,
+        },
+        OrdinalRule::Italian => match n {
+            8 | 11 | 80 | 800 => Many,
+            _ => Other,
+        },
+        OrdinalRule::Hindi => match n {
+            1 => One,
+            2 | 3 => Two,
+            4 => Few,
+            6 => Many,
+            _ => Other,
+        },
+        OrdinalRule::Bengali => match n {
+            1 | 5 | 7..=10 => One,
+            2 | 3 => Two,
+            4 => Few,
+            6 => Many,
+            _ => Other,
+        },
+        OrdinalRule::Marathi => match n {
+            1 => One,
+            2 | 3 => Two,
+            4 => Few,
+            _ => Other,
+        },
+        OrdinalRule::Nepali => match n {
+            1..=4 => One,
+            _ => Other,
+        },
+        OrdinalRule::Ukrainian => match (i10, i100) {
+            (3, i100) if i100 != 13 => Few,
+            _ => Other,
+        },
+        OrdinalRule::Belarusian => match (i10, i100) {
+            (2 | 3, i100) if i100 != 12 && i100 != 13 => Few,
+            _ => Other,
+        },
+        OrdinalRule::Kazakh => match (n, i10) {
+            (_, 6 | 9) => Many,
+            (n, 0) if n != 0 => Many,
+            _ => Other,
+        },
+        OrdinalRule::Turkmen => match (n, i10) {
+            (10, _) | (_, 6 | 9) => Few,
+            _ => Other,
+        },
+        OrdinalRule::Albanian => match (n, i10, i100) {
+            (1, _, _) => One,
+            (_, 4, i100) if i100 != 14 => Many,
+            _ => Other,
+        },
+        OrdinalRule::Georgian => match (n, i100) {
+            (1, _) => One,
+            (0, _) | (_, 2..=20 | 40 | 60 | 80) => Many,
+            _ => Other,
+        },
+        OrdinalRule::Macedonian => match (i10, i100) {
+            (1, i100) if i100 != 11 => One,
+            (2, i100) if i100 != 12 => Two,
+            (7 | 8, i100) if i100 != 17 && i100 != 18 => Many,
+            _ => Other,
+        },
+        OrdinalRule::Azerbaijani => match (n, i10, i100) {
+            (_, 1 | 2 | 5 | 7 | 8, _) | (_, _, 20 | 50 | 70 | 80) => One,
+            (_, 3 | 4, _) => Few,
+            (n, _, _) if n % 1000 != 0 && n % 100 == 0 => Few,
+            (0, _, _) | (_, 6, _) | (_, _, 40 | 60 | 90) => Many,
+            _ => Other,
+        },
+        OrdinalRule::ScottishGaelic => match n {
+            1 | 11 => One,
+            2 | 12 => Two,
+            3 | 13 => Few,
+            _ => Other,
+        },
+        OrdinalRule::Welsh => match n {
+            0 | 7 | 8 | 9 => Zero,
+            1 => One,
+            2 => Two,
+            3 | 4 => Few,
+            5 | 6 => Many,
+            _ => Other,
+        },
+    };
+    Some(category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cardinal_categories() {
+        assert_eq!(cardinal_categories("ja"), Some(&[Other][..]));
+        assert_eq!(cardinal_categories("en-US"), Some(&[One, Other][..]));
+        assert_eq!(cardinal_categories("ru"), Some(&[One, Few, Many, Other][..]));
+        assert_eq!(cardinal_categories("pt_PT"), Some(&[One, Many, Other][..]));
+        assert_eq!(cardinal_categories("cy"), Some(&[Zero, One, Two, Few, Many, Other][..]));
+        assert_eq!(cardinal_categories("x-unknown"), None);
+    }
+
+    #[test]
+    fn test_cardinal_category() {
+        assert_eq!(cardinal_category("ru", 1), Some(One));
+        assert_eq!(cardinal_category("ru", 11), Some(Many));
+        assert_eq!(cardinal_category("ru", 22), Some(Few));
+        assert_eq!(cardinal_category("pl", 12), Some(Many));
+        assert_eq!(cardinal_category("ar", 0), Some(Zero));
+        assert_eq!(cardinal_category("ar", 105), Some(Few));
+        assert_eq!(cardinal_category("lv", 10), Some(Zero));
+        assert_eq!(cardinal_category("fr", 0), Some(One));
+        assert_eq!(cardinal_category("es", 1_000_000), Some(Many));
+        assert_eq!(cardinal_category("en", 2), Some(Other));
+        assert_eq!(cardinal_category("ro", 1), Some(One));
+        assert_eq!(cardinal_category("ro", 101), Some(Few));
+        assert_eq!(cardinal_category("ro", 120), Some(Other));
+    }
+
+    #[test]
+    fn test_ordinal_category() {
+        assert_eq!(ordinal_categories("en"), Some(&[One, Two, Few, Other][..]));
+        assert_eq!(ordinal_categories("ja"), Some(&[Other][..]));
+        assert_eq!(ordinal_categories("x-unknown"), None);
+        assert_eq!(ordinal_category("en", 1), Some(One));
+        assert_eq!(ordinal_category("en", 12), Some(Other));
+        assert_eq!(ordinal_category("en", 22), Some(Two));
+        assert_eq!(ordinal_category("en", 113), Some(Other));
+        assert_eq!(ordinal_category("sv", 32), Some(One));
+        assert_eq!(ordinal_category("it", 80), Some(Many));
+        assert_eq!(ordinal_category("fr", 2), Some(Other));
+        assert_eq!(ordinal_categories("uk"), Some(&[Few, Other][..]));
+        assert_eq!(ordinal_category("uk", 23), Some(Few));
+        assert_eq!(ordinal_category("uk", 13), Some(Other));
+        assert_eq!(ordinal_categories("hi"), Some(&[One, Two, Few, Many, Other][..]));
+        assert_eq!(ordinal_category("hi", 3), Some(Two));
+        assert_eq!(ordinal_category("bn", 9), Some(One));
+        assert_eq!(ordinal_category("ka", 40), Some(Many));
+        assert_eq!(ordinal_category("mk", 17), Some(Other));
+        assert_eq!(ordinal_category("mk", 27), Some(Many));
+        assert_eq!(ordinal_category("az", 300), Some(Few));
+        assert_eq!(ordinal_category("az", 1000), Some(Other));
+        assert_eq!(ordinal_category("az", 0), Some(Many));
+        assert_eq!(ordinal_category("kk", 0), Some(Other));
+        assert_eq!(ordinal_category("kk", 10), Some(Many));
+        assert_eq!(ordinal_category("ne", 4), Some(One));
+        assert_eq!(ordinal_category("gd", 12), Some(Two));
+    }
+}
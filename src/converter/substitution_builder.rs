@@ -1,59 +1,102 @@
 use crate::{
-    converter::formatter::{FormatterMode, XCStringFormatter},
-    xcstrings::{self, StringUnit, Substitution, VariationType, VariationValue},
+    converter::{
+        formatter::{FormatterMode, XCStringFormatter},
+        icu_printer,
+    },
+    xcstrings::{LocalizationState, StringUnit, Substitution, VariationType, VariationValue},
 };
 use icu_messageformat_parser::{self, AstElement};
 use linked_hash_map::LinkedHashMap;
 
-pub struct XCStringSubstitutionBuilder {}
+/// The localization states of one locale's message: `message` is its own
+/// state or the converter default, and `variations` overrides it per argument
+/// and case.
+#[derive(Clone, Debug, Default)]
+pub struct LocalizationStates {
+    pub message: LocalizationState,
+    pub variations: LinkedHashMap<String, LinkedHashMap<String, LocalizationState>>,
+}
+
+impl LocalizationStates {
+    /// The state of the variation for `argument`, looked up by each of `cases`
+    /// in turn (e.g. `=0` and then `zero`).
+    pub fn variation(&self, argument: &str, cases: &[&str]) -> LocalizationState {
+        self.variations
+            .get(argument)
+            .and_then(|states| cases.iter().find_map(|case| states.get(*case)))
+            .unwrap_or(&self.message)
+            .clone()
+    }
+}
+
+#[derive(Default)]
+pub struct XCStringSubstitutionBuilder {
+    states: LocalizationStates,
+}
 
 impl XCStringSubstitutionBuilder {
-    pub fn new() -> XCStringSubstitutionBuilder {
-        XCStringSubstitutionBuilder {}
+    pub fn with_states(states: LocalizationStates) -> XCStringSubstitutionBuilder {
+        XCStringSubstitutionBuilder { states }
     }
 
-    pub fn build(&self, elements: Vec<AstElement>) -> Result<LinkedHashMap<String, Substitution>, String> {
-        let mut formatter = XCStringFormatter::new(FormatterMode::Plural);
+    /// Builds substitutions for the plural and select `elements`, formatting their
+    /// branches with `formatter` so that arguments other than the plural's own
+    /// number keep the positions assigned for the whole message. Plurals nested
+    /// in plural branches get top-level substitutions of their own, see
+    /// `nested_plurals`.
+    pub fn build(
+        &self,
+        mut elements: Vec<AstElement>,
+        formatter: &mut XCStringFormatter,
+    ) -> Result<LinkedHashMap<String, Substitution>, String> {
         let mut result = LinkedHashMap::new();
+        let nested = nested_plurals(&elements)?;
+        elements.extend(nested);
         
-        for (index, element) in elements.iter().enumerate() {
+        for element in elements.iter() {
             match element {
                 AstElement::Plural {
                     value,
                     plural_type: _,
                     span: _,
-                    offset: _,
+                    offset,
                     options,
                 } => {
                     let mut plural_map = LinkedHashMap::new();
-                    for (key, value) in &options.0 {
-                        let key_format = KeyFormat::from_string(&key.to_string())?;
-                        let formatted_results: Result<Vec<String>, String> = value
-                            .value
-                            .iter()
-                            .map(|element| formatter.format(element))
-                            .collect();
-                        let formatted_strings = formatted_results?
-                            .join("");
+                    let mut selectors: Vec<(&str, &str)> = Vec::with_capacity(options.0.len());
+                    let previous_mode = formatter.set_mode(FormatterMode::Plural(value.clone()));
+                    for (key, option) in &options.0 {
+                        let key_format = KeyFormat::from_string(&offset_selector(key, *offset))?;
+                        let localization_state = self.states.variation(value, &[key, key_format.as_str()]);
+                        // `=0` と `zero` のように同じバリエーションになるケースを検出する
+                        if let Some((existing, _)) = selectors.iter().find(|(_, variation)| *variation == key_format.as_str()) {
+                            return Err(format!(
+                                "Plural '{}' has both '{}' and '{}' cases, which would both become the '{}' variation",
+                                value, existing, key, key_format.as_str()
+                            ));
+                        }
+                        selectors.push((key, key_format.as_str()));
+                        let formatted_strings = formatter.format_elements(&option.value)?;
                         plural_map.insert(
-                            key_format.to_string(),
+                            key_format.as_str().to_string(),
                             VariationValue {
                                 string_unit: StringUnit {
-                                    localization_state: xcstrings::LocalizationState::Translated,
+                                    localization_state,
                                     value: formatted_strings,
                                 },
                             },
                         );
                     }
+                    formatter.set_mode(previous_mode);
                     
-                    let arg_num = index.checked_add(1)
-                        .ok_or_else(|| format!("Argument number overflow for plural '{}'", value))?;
+                    let arg_num = formatter.position_for(value, "lld")?;
+                    let format_specifier = formatter.specifier_for(value).unwrap_or("lld").to_string();
                     
                     result.insert(
                         value.clone(),
                         Substitution {
                             arg_num,
-                            format_specifier: "lld".to_string(),
+                            format_specifier,
                             variations: VariationType::Plural(plural_map),
                         },
                     );
@@ -64,33 +107,27 @@ impl XCStringSubstitutionBuilder {
                     ..
                 } => {
                     let mut select_map = LinkedHashMap::new();
-                    for (key, value) in &options.0 {
-                        let formatted_results: Result<Vec<String>, String> = value
-                            .value
-                            .iter()
-                            .map(|element| formatter.format(element))
-                            .collect();
-                        let formatted_strings = formatted_results?
-                            .join("");
+                    for (key, option) in &options.0 {
+                        let formatted_strings = formatter.format_elements(&option.value)?;
                         select_map.insert(
                             key.to_string(),
                             VariationValue {
                                 string_unit: StringUnit {
-                                    localization_state: xcstrings::LocalizationState::Translated,
+                                    localization_state: self.states.variation(value, &[key]),
                                     value: formatted_strings,
                                 },
                             },
                         );
                     }
                     
-                    let arg_num = index.checked_add(1)
-                        .ok_or_else(|| format!("Argument number overflow for select '{}'", value))?;
+                    let arg_num = formatter.position_for(value, "@")?;
+                    let format_specifier = formatter.specifier_for(value).unwrap_or("@").to_string();
                     
                     result.insert(
                         value.clone(),
                         Substitution {
                             arg_num,
-                            format_specifier: "@".to_string(),
+                            format_specifier,
                             variations: VariationType::Select(select_map),
                         },
                     );
@@ -103,9 +140,116 @@ impl XCStringSubstitutionBuilder {
     }
 }
 
+/// The plurals nested in the branches of the plurals in `elements`, at any depth,
+/// which the builder hoists into top-level substitutions: a variation value is a
+/// plain string unit, so `%#@folders@` inside a `files` variation has to refer to
+/// a substitution of the whole localization. That is only safe when every
+/// occurrence of the nested plural reads the same, and it isn't nested in a
+/// plural on its own argument.
+pub fn nested_plurals<'s>(elements: &[AstElement<'s>]) -> Result<Vec<AstElement<'s>>, String> {
+    let mut nested = Vec::new();
+    collect_nested_plurals(elements, &mut Vec::new(), &mut nested)?;
+
+    // トップレベルにも同じ引数の plural があれば、内容が同じ場合に限りそちらを使う
+    let mut hoisted = Vec::with_capacity(nested.len());
+    for (outer, plural) in nested {
+        let argument = plural_argument(&plural);
+        match elements.iter().find(|element| plural_argument(element) == argument) {
+            Some(top_level) if !same_plural(top_level, &plural) => {
+                return Err(format!(
+                    "Plural '{}' inside plural '{}' reads differently from the '{}' plural outside it, but String Catalogs need one substitution per argument",
+                    argument.unwrap_or(""),
+                    outer,
+                    argument.unwrap_or("")
+                ));
+            }
+            Some(_) => {}
+            None => hoisted.push(plural),
+        }
+    }
+    Ok(hoisted)
+}
+
+fn collect_nested_plurals<'s>(
+    elements: &[AstElement<'s>],
+    enclosing: &mut Vec<String>,
+    nested: &mut Vec<(String, AstElement<'s>)>,
+) -> Result<(), String> {
+    for element in elements {
+        match element {
+            AstElement::Plural { value, options, .. } => {
+                if let Some(outer) = enclosing.last() {
+                    if enclosing.contains(value) {
+                        return Err(format!(
+                            "Plural '{}' is nested inside a plural on the same argument, which String Catalogs can't represent",
+                            value
+                        ));
+                    }
+                    match nested.iter().find(|(_, plural)| plural_argument(plural) == Some(value.as_str())) {
+                        Some((_, existing)) if !same_plural(existing, element) => {
+                            return Err(format!(
+                                "Plural '{}' reads differently in the branches of plural '{}', but String Catalogs need one substitution per argument; use the same cases everywhere it appears",
+                                value, outer
+                            ));
+                        }
+                        Some(_) => {}
+                        None => nested.push((outer.clone(), element.clone())),
+                    }
+                }
+                enclosing.push(value.clone());
+                for (_, option) in &options.0 {
+                    collect_nested_plurals(&option.value, enclosing, nested)?;
+                }
+                enclosing.pop();
+            }
+            AstElement::Select { options, .. } => {
+                for (_, option) in &options.0 {
+                    collect_nested_plurals(&option.value, enclosing, nested)?;
+                }
+            }
+            AstElement::Tag { children, .. } => collect_nested_plurals(children, enclosing, nested)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn plural_argument<'a>(element: &'a AstElement) -> Option<&'a str> {
+    match element {
+        AstElement::Plural { value, .. } => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+/// Compares plurals as ICU text, since `#` carries its location.
+fn same_plural(a: &AstElement, b: &AstElement) -> bool {
+    icu_printer::print(std::slice::from_ref(a)) == icu_printer::print(std::slice::from_ref(b))
+}
+
+/// With an offset the variations receive `n - offset`, so an exact `=k` case
+/// has to match `k - offset`. Offsets that can't be rewritten are rejected
+/// before conversion.
+fn offset_selector(selector: &str, offset: i64) -> String {
+    match selector.strip_prefix('=').and_then(|n| n.parse::<i64>().ok()) {
+        Some(exact) if offset != 0 => format!("={}", exact - offset),
+        _ => selector.to_string(),
+    }
+}
+
+/// The value of an exact `=N` case that has no String Catalog variation once the
+/// plural's offset is applied, i.e. anything but `zero` and `one`. Cases below the
+/// offset are left to the offset check.
+pub fn unrepresentable_exact_case(selector: &str, offset: i64) -> Option<i64> {
+    let exact = selector.strip_prefix('=')?.parse::<i64>().ok()?;
+    (exact - offset >= 2).then_some(exact)
+}
+
 enum KeyFormat {
     Zero,
     One,
+    Two,
+    Few,
+    Many,
     Other,
 }
 
@@ -114,15 +258,18 @@ impl KeyFormat {
         match key.as_str() {
             "zero" => Ok(KeyFormat::Zero),
             "one" => Ok(KeyFormat::One),
-            "two" => Err("String Catalog doesn't support custom keys starting with 'two'".to_string()),
-            "few" => Err("String Catalog doesn't support custom keys starting with 'few'".to_string()),
-            "many" => Err("String Catalog doesn't support custom keys starting with 'many'".to_string()),
+            "two" => Ok(KeyFormat::Two),
+            "few" => Ok(KeyFormat::Few),
+            "many" => Ok(KeyFormat::Many),
             "other" => Ok(KeyFormat::Other),
             "=0" => Ok(KeyFormat::Zero), // "zero" is an alias for "=0"
             "=1" => Ok(KeyFormat::One),  // "one" is an alias for "=1"
             _ => {
                 if key.as_str().starts_with("=") {
-                    Err("String Catalog doesn't support custom keys starting with '=' except for zero and one".to_string())
+                    Err(format!(
+                        "String Catalog doesn't support custom keys starting with '=' except for zero and one (found '{}'); split them into separate keys or fold them with the exact match handling option",
+                        key
+                    ))
                 } else {
                     Err(format!("Unexpected key format: '{}'", key))
                 }
@@ -130,11 +277,14 @@ impl KeyFormat {
         }
     }
 
-    fn to_string(&self) -> String {
+    fn as_str(&self) -> &'static str {
         match self {
-            KeyFormat::Zero => "zero".to_string(),
-            KeyFormat::One => "one".to_string(),
-            KeyFormat::Other => "other".to_string(),
+            KeyFormat::Zero => "zero",
+            KeyFormat::One => "one",
+            KeyFormat::Two => "two",
+            KeyFormat::Few => "few",
+            KeyFormat::Many => "many",
+            KeyFormat::Other => "other",
         }
     }
 }
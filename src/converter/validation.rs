@@ -0,0 +1,835 @@
+use crate::converter::formatter::MARKDOWN_TAGS;
+use crate::converter::plural_rules::{self, PluralCategory};
+use crate::converter::substitution_builder::unrepresentable_exact_case;
+use crate::models::{
+    Diagnostic, Severity, DiagnosticKind, ExactMatchHandling, OrdinalHandling, Placeholder, PlaceholderType,
+    PluralOffsetHandling,
+};
+use icu_messageformat_parser::AstElement;
+use linked_hash_map::LinkedHashMap;
+use std::collections::BTreeSet;
+
+/// The cases of each select argument, in the order they first appear.
+pub type SelectCases = Vec<(String, Vec<String>)>;
+
+/// Whether a plural is a `selectordinal`. The parser doesn't export its
+/// `PluralType`, so the variant is told apart by its `Debug` name; this is the
+/// one place that depends on it.
+pub fn is_ordinal(plural_type: &impl std::fmt::Debug) -> bool {
+    format!("{:?}", plural_type) == "Ordinal"
+}
+
+/// Checks every cardinal plural in `elements` against the CLDR categories of
+/// `locale`. Locales without embedded rules are skipped.
+pub fn validate_plural_categories(
+    key: &str,
+    locale: &str,
+    elements: &[AstElement],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for element in elements {
+        match element {
+            AstElement::Plural { value, plural_type, options, .. } => {
+                if !is_ordinal(plural_type) {
+                    let selectors: Vec<&str> = options.0.iter().map(|(selector, _)| *selector).collect();
+                    validate_cardinal_selectors(key, locale, value, &selectors, diagnostics);
+                }
+                for (_, option) in &options.0 {
+                    validate_plural_categories(key, locale, &option.value, diagnostics);
+                }
+            }
+            AstElement::Select { options, .. } => {
+                for (_, option) in &options.0 {
+                    validate_plural_categories(key, locale, &option.value, diagnostics);
+                }
+            }
+            AstElement::Tag { children, .. } => {
+                validate_plural_categories(key, locale, children, diagnostics);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn validate_cardinal_selectors(
+    key: &str,
+    locale: &str,
+    argument: &str,
+    selectors: &[&str],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let categories = match plural_rules::cardinal_categories(locale) {
+        Some(categories) => categories,
+        None => return,
+    };
+
+    // `=0` and `=1` become the `zero` and `one` variations during conversion.
+    let mut provided: Vec<PluralCategory> = selectors
+        .iter()
+        .filter_map(|selector| PluralCategory::from_keyword(selector))
+        .collect();
+    if selectors.contains(&"=0") {
+        provided.push(PluralCategory::Zero);
+    }
+    if selectors.contains(&"=1") {
+        provided.push(PluralCategory::One);
+    }
+
+    for category in categories {
+        if !provided.contains(category) {
+            diagnostics.push(Diagnostic::warning(
+                key,
+                locale,
+                DiagnosticKind::MissingPluralCategory {
+                    argument: argument.to_string(),
+                    category: category.as_str().to_string(),
+                },
+            ));
+        }
+    }
+
+    for selector in selectors {
+        let category = match PluralCategory::from_keyword(selector) {
+            Some(category) => category,
+            None => continue,
+        };
+        if categories.contains(&category) {
+            continue;
+        }
+        let kind = if category == PluralCategory::Zero {
+            DiagnosticKind::ZeroCategorySemantics {
+                argument: argument.to_string(),
+                selector: selector.to_string(),
+                detail: "ICU never selects 'zero' in this locale, but String Catalogs use it for 0; use '=0' to make the intent explicit".to_string(),
+            }
+        } else {
+            DiagnosticKind::UnreachablePluralCategory {
+                argument: argument.to_string(),
+                category: category.as_str().to_string(),
+            }
+        };
+        diagnostics.push(Diagnostic::warning(key, locale, kind));
+    }
+
+    if selectors.contains(&"=0") && categories.contains(&PluralCategory::Zero) {
+        let also_zero = (1..=100u64)
+            .find(|n| plural_rules::cardinal_category(locale, *n) == Some(PluralCategory::Zero));
+        if let Some(n) = also_zero {
+            diagnostics.push(Diagnostic::warning(
+                key,
+                locale,
+                DiagnosticKind::ZeroCategorySemantics {
+                    argument: argument.to_string(),
+                    selector: "=0".to_string(),
+                    detail: format!(
+                        "'=0' is converted to the 'zero' variation, which this locale also selects for other numbers such as {}",
+                        n
+                    ),
+                },
+            ));
+        }
+    }
+}
+
+/// Checks every `selectordinal` in `elements`. When ordinals are split into keys,
+/// their cases are checked against the CLDR ordinal categories of `locale`; when
+/// they are converted as cardinal plurals, a warning names a number that would
+/// pick a different case than intended.
+pub fn validate_ordinals(
+    key: &str,
+    locale: &str,
+    elements: &[AstElement],
+    handling: OrdinalHandling,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for element in elements {
+        match element {
+            AstElement::Plural { value, plural_type, options, .. } => {
+                if is_ordinal(plural_type) {
+                    let selectors: Vec<&str> = options.0.iter().map(|(selector, _)| *selector).collect();
+                    match handling {
+                        OrdinalHandling::Split => {
+                            validate_ordinal_selectors(key, locale, value, &selectors, diagnostics)
+                        }
+                        OrdinalHandling::Cardinal => {
+                            validate_ordinal_as_cardinal(key, locale, value, &selectors, diagnostics)
+                        }
+                    }
+                }
+                for (_, option) in &options.0 {
+                    validate_ordinals(key, locale, &option.value, handling, diagnostics);
+                }
+            }
+            AstElement::Select { options, .. } => {
+                for (_, option) in &options.0 {
+                    validate_ordinals(key, locale, &option.value, handling, diagnostics);
+                }
+            }
+            AstElement::Tag { children, .. } => {
+                validate_ordinals(key, locale, children, handling, diagnostics);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn validate_ordinal_selectors(
+    key: &str,
+    locale: &str,
+    argument: &str,
+    selectors: &[&str],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let categories = match plural_rules::ordinal_categories(locale) {
+        Some(categories) => categories,
+        None => return,
+    };
+    for category in categories {
+        if !selectors.contains(&category.as_str()) {
+            diagnostics.push(Diagnostic::warning(
+                key,
+                locale,
+                DiagnosticKind::MissingPluralCategory {
+                    argument: argument.to_string(),
+                    category: category.as_str().to_string(),
+                },
+            ));
+        }
+    }
+    for selector in selectors {
+        if let Some(category) = PluralCategory::from_keyword(selector) {
+            if !categories.contains(&category) {
+                diagnostics.push(Diagnostic::warning(
+                    key,
+                    locale,
+                    DiagnosticKind::UnreachablePluralCategory {
+                        argument: argument.to_string(),
+                        category: category.as_str().to_string(),
+                    },
+                ));
+            }
+        }
+    }
+}
+
+fn validate_ordinal_as_cardinal(
+    key: &str,
+    locale: &str,
+    argument: &str,
+    selectors: &[&str],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    // The case a number ends up in, given the category its rule selects.
+    let case_for = |category: PluralCategory| {
+        if selectors.contains(&category.as_str()) {
+            category.as_str()
+        } else {
+            "other"
+        }
+    };
+    let detail = if plural_rules::ordinal_categories(locale).is_none() || plural_rules::cardinal_categories(locale).is_none() {
+        "this locale has no embedded plural rules, so the cases may not match the ordinal rules".to_string()
+    } else {
+        let mismatch = (1..=100u64).find_map(|n| {
+            let intended = case_for(plural_rules::ordinal_category(locale, n)?);
+            let actual = case_for(plural_rules::cardinal_category(locale, n)?);
+            (intended != actual).then_some((n, intended, actual))
+        });
+        match mismatch {
+            Some((n, intended, actual)) => format!(
+                "{} selects the '{}' case instead of '{}'",
+                n, actual, intended
+            ),
+            None => return,
+        }
+    };
+    diagnostics.push(Diagnostic::warning(
+        key,
+        locale,
+        DiagnosticKind::OrdinalAsCardinal {
+            argument: argument.to_string(),
+            detail,
+        },
+    ));
+}
+
+/// Reports plurals with an `offset:` that can't be converted under `handling`
+/// as errors. `elements` is the whole message, since a rewritten plural changes
+/// the value its argument receives everywhere in the message.
+pub fn validate_plural_offsets(
+    key: &str,
+    locale: &str,
+    elements: &[AstElement],
+    handling: PluralOffsetHandling,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    validate_plural_offsets_within(key, locale, elements, elements, handling, diagnostics);
+}
+
+fn validate_plural_offsets_within(
+    key: &str,
+    locale: &str,
+    message: &[AstElement],
+    elements: &[AstElement],
+    handling: PluralOffsetHandling,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for element in elements {
+        match element {
+            AstElement::Plural { value, offset, options, .. } => {
+                if *offset != 0 {
+                    let mut error = |detail: String| {
+                        diagnostics.push(Diagnostic::error(
+                            key,
+                            locale,
+                            DiagnosticKind::PluralOffset {
+                                argument: value.to_string(),
+                                offset: *offset,
+                                detail,
+                            },
+                        ))
+                    };
+                    match handling {
+                        PluralOffsetHandling::Reject => error(
+                            "String Catalogs have no plural offset; remove it or convert with the 'rewrite' offset handling".to_string(),
+                        ),
+                        PluralOffsetHandling::Rewrite => {
+                            for (selector, _) in &options.0 {
+                                let exact = selector.strip_prefix('=').and_then(|n| n.parse::<i64>().ok());
+                                if let Some(exact) = exact.filter(|exact| *exact < *offset) {
+                                    error(format!(
+                                        "case '{}' would have to match {} after the offset, which String Catalogs can't express",
+                                        selector,
+                                        exact - offset
+                                    ));
+                                }
+                            }
+                            if uses_argument(message, value) {
+                                error(format!(
+                                    "'{{{}}}' would show the value after the offset; use '#' inside the plural instead",
+                                    value
+                                ));
+                            }
+                        }
+                    }
+                }
+                for (_, option) in &options.0 {
+                    validate_plural_offsets_within(key, locale, message, &option.value, handling, diagnostics);
+                }
+            }
+            AstElement::Select { options, .. } => {
+                for (_, option) in &options.0 {
+                    validate_plural_offsets_within(key, locale, message, &option.value, handling, diagnostics);
+                }
+            }
+            AstElement::Tag { children, .. } => {
+                validate_plural_offsets_within(key, locale, message, children, handling, diagnostics);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether `argument` appears as a plain or number argument anywhere in `elements`.
+fn uses_argument(elements: &[AstElement], argument: &str) -> bool {
+    elements.iter().any(|element| match element {
+        AstElement::Argument { value, .. } | AstElement::Number { value, .. } => value == argument,
+        AstElement::Plural { options, .. } | AstElement::Select { options, .. } => {
+            options.0.iter().any(|(_, option)| uses_argument(&option.value, argument))
+        }
+        AstElement::Tag { children, .. } => uses_argument(children, argument),
+        _ => false,
+    })
+}
+
+/// Checks the exact `=N` cases of every plural: cases that collide with a
+/// category (`=0` and `zero`) are errors, and cases without a variation of their
+/// own are reported according to `handling`.
+pub fn validate_exact_cases(
+    key: &str,
+    locale: &str,
+    elements: &[AstElement],
+    handling: ExactMatchHandling,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for element in elements {
+        match element {
+            AstElement::Plural { value, offset, options, .. } => {
+                let selectors: Vec<&str> = options.0.iter().map(|(selector, _)| *selector).collect();
+                // With an offset, `=k` becomes the variation for `k - offset`.
+                for (n, keyword) in [(0, "zero"), (1, "one")] {
+                    let exact = format!("={}", n + offset);
+                    if selectors.contains(&exact.as_str()) && selectors.contains(&keyword) {
+                        diagnostics.push(Diagnostic::error(
+                            key,
+                            locale,
+                            DiagnosticKind::ExactMatchCase {
+                                argument: value.to_string(),
+                                selector: exact.clone(),
+                                detail: format!(
+                                    "both '{}' and '{}' would become the '{}' variation; keep only one of them",
+                                    exact, keyword, keyword
+                                ),
+                            },
+                        ));
+                    }
+                }
+
+                for selector in &selectors {
+                    let exact = match unrepresentable_exact_case(selector, *offset) {
+                        Some(exact) => exact,
+                        None => continue,
+                    };
+                    let (severity, detail) = match handling {
+                        ExactMatchHandling::Error => (
+                            Severity::Error,
+                            "String Catalogs only have exact cases for 0 and 1; split it into its own key or fold it with the exact match handling option".to_string(),
+                        ),
+                        ExactMatchHandling::SplitKeys => continue,
+                        ExactMatchHandling::Fold => {
+                            let category = fold_category(locale, exact - offset);
+                            let detail = if selectors.contains(&category.as_str()) {
+                                format!("dropped, since the '{}' case it folds into already exists", category.as_str())
+                            } else {
+                                format!("folded into '{}', so every number in that category shows its text", category.as_str())
+                            };
+                            (Severity::Warning, detail)
+                        }
+                    };
+                    diagnostics.push(Diagnostic {
+                        severity,
+                        key: key.to_string(),
+                        locale: Some(locale.to_string()),
+                        kind: DiagnosticKind::ExactMatchCase {
+                            argument: value.to_string(),
+                            selector: selector.to_string(),
+                            detail,
+                        },
+                    });
+                }
+
+                for (_, option) in &options.0 {
+                    validate_exact_cases(key, locale, &option.value, handling, diagnostics);
+                }
+            }
+            AstElement::Select { options, .. } => {
+                for (_, option) in &options.0 {
+                    validate_exact_cases(key, locale, &option.value, handling, diagnostics);
+                }
+            }
+            AstElement::Tag { children, .. } => {
+                validate_exact_cases(key, locale, children, handling, diagnostics);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The category an exact case for `n` folds into: the one the locale's cardinal
+/// rules select for `n`, or `other` without embedded rules.
+pub fn fold_category(locale: &str, n: i64) -> PluralCategory {
+    u64::try_from(n)
+        .ok()
+        .and_then(|n| plural_rules::cardinal_category(locale, n))
+        .unwrap_or(PluralCategory::Other)
+}
+
+/// Reports printf-style directives such as `%d` or `%@` typed into the literal
+/// text of an ICU message. The converter escapes them as literal percent signs,
+/// which is rarely what the author meant.
+pub fn validate_printf_sequences(
+    key: &str,
+    locale: &str,
+    elements: &[AstElement],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for element in elements {
+        match element {
+            AstElement::Literal { value, .. } => {
+                for sequence in find_printf_sequences(value) {
+                    diagnostics.push(Diagnostic::warning(
+                        key,
+                        locale,
+                        DiagnosticKind::PrintfStyleSequence { sequence },
+                    ));
+                }
+            }
+            AstElement::Plural { options, .. } | AstElement::Select { options, .. } => {
+                for (_, option) in &options.0 {
+                    validate_printf_sequences(key, locale, &option.value, diagnostics);
+                }
+            }
+            AstElement::Tag { children, .. } => {
+                validate_printf_sequences(key, locale, children, diagnostics);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reports tags the conversion can't represent: with `markdown_tags` those
+/// without a Markdown equivalent, otherwise every tag the parser kept.
+pub fn validate_tags(
+    key: &str,
+    locale: &str,
+    elements: &[AstElement],
+    markdown_tags: bool,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for element in elements {
+        match element {
+            AstElement::Tag { value, children, .. } => {
+                if !markdown_tags {
+                    diagnostics.push(Diagnostic::error(
+                        key,
+                        locale,
+                        DiagnosticKind::UnsupportedTag {
+                            tag: value.to_string(),
+                            detail: "String Catalogs have no tags; enable Markdown tags or parse tags as text".to_string(),
+                        },
+                    ));
+                } else if !MARKDOWN_TAGS.contains(value) {
+                    diagnostics.push(Diagnostic::error(
+                        key,
+                        locale,
+                        DiagnosticKind::UnsupportedTag {
+                            tag: value.to_string(),
+                            detail: "only <b>, <i> and <link> have a Markdown equivalent".to_string(),
+                        },
+                    ));
+                }
+                validate_tags(key, locale, children, markdown_tags, diagnostics);
+            }
+            AstElement::Plural { options, .. } | AstElement::Select { options, .. } => {
+                for (_, option) in &options.0 {
+                    validate_tags(key, locale, &option.value, markdown_tags, diagnostics);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// How a message uses an argument, which decides its format specifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ArgumentUsage {
+    Plain,
+    Number,
+    Date,
+    Time,
+    Plural,
+    Select,
+}
+
+impl ArgumentUsage {
+    fn accepts(&self, placeholder_type: PlaceholderType) -> bool {
+        match self {
+            ArgumentUsage::Plain => true,
+            ArgumentUsage::Number | ArgumentUsage::Plural => placeholder_type.is_number(),
+            ArgumentUsage::Date | ArgumentUsage::Time => placeholder_type == PlaceholderType::DateTime,
+            ArgumentUsage::Select => placeholder_type == PlaceholderType::String,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ArgumentUsage::Plain => "a plain argument",
+            ArgumentUsage::Number => "a number",
+            ArgumentUsage::Date => "a date",
+            ArgumentUsage::Time => "a time",
+            ArgumentUsage::Plural => "a plural",
+            ArgumentUsage::Select => "a select",
+        }
+    }
+}
+
+/// Every argument of `elements` with how it's used, including those inside
+/// plural and select branches. The `<link>` tag counts as a plain argument,
+/// since it becomes one with Markdown tags.
+pub fn argument_usages(elements: &[AstElement], usages: &mut Vec<(String, ArgumentUsage)>) {
+    for element in elements {
+        match element {
+            AstElement::Argument { value, .. } => usages.push((value.clone(), ArgumentUsage::Plain)),
+            AstElement::Number { value, .. } => usages.push((value.clone(), ArgumentUsage::Number)),
+            AstElement::Date { value, .. } => usages.push((value.clone(), ArgumentUsage::Date)),
+            AstElement::Time { value, .. } => usages.push((value.clone(), ArgumentUsage::Time)),
+            AstElement::Plural { value, options, .. } => {
+                usages.push((value.clone(), ArgumentUsage::Plural));
+                for (_, option) in &options.0 {
+                    argument_usages(&option.value, usages);
+                }
+            }
+            AstElement::Select { value, options, .. } => {
+                usages.push((value.clone(), ArgumentUsage::Select));
+                for (_, option) in &options.0 {
+                    argument_usages(&option.value, usages);
+                }
+            }
+            AstElement::Tag { value, children, .. } => {
+                if *value == "link" {
+                    usages.push((value.to_string(), ArgumentUsage::Plain));
+                }
+                argument_usages(children, usages);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reports arguments of `elements` whose declared type doesn't fit how they're
+/// used, e.g. a `String` placeholder used as a plural. Unknown types are left
+/// to `validate_placeholder_declarations`.
+pub fn validate_placeholder_types(
+    key: &str,
+    locale: &str,
+    elements: &[AstElement],
+    placeholders: &LinkedHashMap<String, Placeholder>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut usages = Vec::new();
+    argument_usages(elements, &mut usages);
+    let mut reported = Vec::new();
+    for (argument, usage) in usages {
+        let placeholder_type = placeholders
+            .get(&argument)
+            .and_then(|placeholder| placeholder.placeholder_type.as_ref())
+            .and_then(|placeholder_type| placeholder_type.parse::<PlaceholderType>().ok());
+        match placeholder_type {
+            Some(placeholder_type) if !usage.accepts(placeholder_type) && !reported.contains(&(argument.clone(), usage)) => {
+                diagnostics.push(Diagnostic::error(
+                    key,
+                    locale,
+                    DiagnosticKind::PlaceholderTypeMismatch {
+                        placeholder: argument.clone(),
+                        declared: placeholder_type.to_string(),
+                        usage: usage.describe().to_string(),
+                    },
+                ));
+                reported.push((argument, usage));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Checks the declarations of a key against the arguments its messages use:
+/// unknown types are errors, while placeholders no message uses and arguments
+/// left undeclared are warnings.
+pub fn validate_placeholder_declarations(
+    key: &str,
+    placeholders: &LinkedHashMap<String, Placeholder>,
+    used_arguments: &BTreeSet<String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for (name, placeholder) in placeholders {
+        if let Some(placeholder_type) = &placeholder.placeholder_type {
+            if placeholder_type.parse::<PlaceholderType>().is_err() {
+                diagnostics.push(Diagnostic::key_error(
+                    key,
+                    DiagnosticKind::UnknownPlaceholderType {
+                        placeholder: name.clone(),
+                        placeholder_type: placeholder_type.clone(),
+                    },
+                ));
+            }
+        }
+        if !used_arguments.contains(name) {
+            diagnostics.push(Diagnostic::key_warning(
+                key,
+                DiagnosticKind::UnusedPlaceholder { placeholder: name.clone() },
+            ));
+        }
+    }
+    for argument in used_arguments {
+        if !placeholders.contains_key(argument) {
+            diagnostics.push(Diagnostic::key_warning(
+                key,
+                DiagnosticKind::UndeclaredPlaceholder { argument: argument.clone() },
+            ));
+        }
+    }
+}
+
+/// The cases of every select in `elements` by argument, including selects
+/// nested in plural and select branches. The device select is left out, since
+/// its cases become device variations rather than keys.
+pub fn select_cases(elements: &[AstElement], device_argument: Option<&str>, cases: &mut SelectCases) {
+    for element in elements {
+        match element {
+            AstElement::Select { value, options, .. } => {
+                if device_argument != Some(value.as_str()) {
+                    let index = match cases.iter().position(|(argument, _)| argument == value) {
+                        Some(index) => index,
+                        None => {
+                            cases.push((value.clone(), Vec::new()));
+                            cases.len() - 1
+                        }
+                    };
+                    for (case_key, _) in &options.0 {
+                        if !cases[index].1.iter().any(|existing| existing == case_key) {
+                            cases[index].1.push(case_key.to_string());
+                        }
+                    }
+                }
+                for (_, option) in &options.0 {
+                    select_cases(&option.value, device_argument, cases);
+                }
+            }
+            AstElement::Plural { options, .. } => {
+                for (_, option) in &options.0 {
+                    select_cases(&option.value, device_argument, cases);
+                }
+            }
+            AstElement::Tag { children, .. } => select_cases(children, device_argument, cases),
+            _ => {}
+        }
+    }
+}
+
+/// Compares the select cases of each locale with their union across locales,
+/// which is what selects are split on. Cases a locale lacks fall back to
+/// `other` there, an error when `strict`; cases it adds beyond
+/// `reference_locale` are warnings.
+pub fn validate_select_cases(
+    key: &str,
+    reference_locale: &str,
+    locale_cases: &[(String, SelectCases)],
+    strict: bool,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut union: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (_, cases) in locale_cases {
+        for (argument, argument_cases) in cases {
+            let index = match union.iter().position(|(existing, _)| existing == argument) {
+                Some(index) => index,
+                None => {
+                    union.push((argument, Vec::new()));
+                    union.len() - 1
+                }
+            };
+            for case_key in argument_cases {
+                if !union[index].1.contains(&case_key.as_str()) {
+                    union[index].1.push(case_key);
+                }
+            }
+        }
+    }
+    let reference = locale_cases
+        .iter()
+        .find(|(locale, _)| locale == reference_locale)
+        .map(|(_, cases)| cases);
+
+    for (locale, cases) in locale_cases {
+        for (argument, argument_cases) in cases {
+            let all_cases = union
+                .iter()
+                .find(|(existing, _)| existing == argument)
+                .map(|(_, all_cases)| all_cases.as_slice())
+                .unwrap_or_default();
+            let missing: Vec<String> = all_cases
+                .iter()
+                .filter(|case_key| !argument_cases.iter().any(|existing| existing == *case_key))
+                .map(|case_key| case_key.to_string())
+                .collect();
+            if !missing.is_empty() {
+                let kind = DiagnosticKind::MissingSelectCases { argument: argument.clone(), cases: missing };
+                diagnostics.push(if strict {
+                    Diagnostic::error(key, locale, kind)
+                } else {
+                    Diagnostic::warning(key, locale, kind)
+                });
+            }
+
+            if locale == reference_locale {
+                continue;
+            }
+            let reference_cases = match reference.and_then(|reference| reference.iter().find(|(existing, _)| existing == argument)) {
+                Some((_, reference_cases)) => reference_cases,
+                None => continue,
+            };
+            let extra: Vec<String> = argument_cases
+                .iter()
+                .filter(|case_key| !reference_cases.contains(case_key))
+                .cloned()
+                .collect();
+            if !extra.is_empty() {
+                diagnostics.push(Diagnostic::warning(
+                    key,
+                    locale,
+                    DiagnosticKind::ExtraSelectCases {
+                        argument: argument.clone(),
+                        cases: extra,
+                        reference_locale: reference_locale.to_string(),
+                    },
+                ));
+            }
+        }
+    }
+}
+
+fn find_printf_sequences(text: &str) -> Vec<String> {
+    const CONVERSIONS: &str = "@dDiuUxXoOfFeEgGcCsSpaA";
+    let chars: Vec<char> = text.chars().collect();
+    let mut sequences = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        if chars[index] != '%' {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        let mut cursor = index + 1;
+
+        // `%%` and `%#@name@` are String Catalog syntax rather than ICU text.
+        if chars.get(cursor) == Some(&'%') {
+            sequences.push("%%".to_string());
+            index = cursor + 1;
+            continue;
+        }
+        if chars.get(cursor) == Some(&'#') && chars.get(cursor + 1) == Some(&'@') {
+            if let Some(end) = chars[cursor + 2..].iter().position(|c| *c == '@') {
+                let end = cursor + 2 + end;
+                sequences.push(chars[start..=end].iter().collect());
+                index = end + 1;
+                continue;
+            }
+        }
+
+        // Optional `N$` position, then flags, width, precision and length modifier.
+        let digits = chars[cursor..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if digits > 0 && chars.get(cursor + digits) == Some(&'$') {
+            cursor += digits + 1;
+        }
+        cursor += chars[cursor..].iter().take_while(|c| "-+0#'".contains(**c)).count();
+        cursor += chars[cursor..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if chars.get(cursor) == Some(&'.') {
+            cursor += 1;
+            cursor += chars[cursor..].iter().take_while(|c| c.is_ascii_digit()).count();
+        }
+        cursor += chars[cursor..].iter().take(2).take_while(|c| "hlqLztj".contains(**c)).count();
+
+        match chars.get(cursor) {
+            Some(conversion) if CONVERSIONS.contains(*conversion) => {
+                sequences.push(chars[start..=cursor].iter().collect());
+                index = cursor + 1;
+            }
+            _ => index = start + 1,
+        }
+    }
+
+    sequences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_printf_sequences;
+
+    #[test]
+    fn test_find_printf_sequences() {
+        assert!(find_printf_sequences("50% off, 100 %").is_empty());
+        assert_eq!(find_printf_sequences("Hello %@, you have %d items"), vec!["%@", "%d"]);
+        assert_eq!(find_printf_sequences("%1$@ paid %2$.2f (%lld) %%"), vec!["%1$@", "%2$.2f", "%lld", "%%"]);
+        assert_eq!(find_printf_sequences("%#@count@ left"), vec!["%#@count@"]);
+    }
+}
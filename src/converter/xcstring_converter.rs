@@ -1,10 +1,19 @@
-use crate::models::{self, ConverterOptions};
+use crate::models::{self, ConverterOptions, ExactMatchHandling, MissingSourceHandling, OrdinalHandling};
 use crate::converter::formatter::{FormatterMode, XCStringFormatter};
-use crate::converter::substitution_builder::XCStringSubstitutionBuilder;
+use crate::converter::substitution_builder::{
+    nested_plurals, unrepresentable_exact_case, LocalizationStates, XCStringSubstitutionBuilder,
+};
+use crate::converter::icu_printer;
+use crate::converter::validation::{self, ArgumentUsage};
 use crate::xcstrings;
 use icu_messageformat_parser::{self, AstElement};
 use linked_hash_map::LinkedHashMap;
 use rayon::prelude::*;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+
+/// How each argument of a message is used, keyed by name.
+type ArgumentKinds = HashMap<String, BTreeSet<ArgumentUsage>>;
 
 #[derive(Debug)]
 pub struct XCStringConverter {
@@ -35,27 +44,21 @@ impl XCStringConverter {
         };
         
         for message in messages.iter() {
+            let message = match self.with_source_language(message)? {
+                Some(message) => message,
+                None => continue,
+            };
             // 変数の一貫性をチェック
-            if let Err(error) = self.validate_variable_consistency(message) {
-                return Err(error);
-            }
+            self.validate_states(&message)?;
+            self.check_placeholders(&message)?;
+            self.check_select_cases(&message)?;
+            self.validate_variable_consistency(&message)?;
+            self.validate_tag_consistency(&message)?;
             
-            if self.has_select_elements(message) {
-                if self.converter_options.split_select_elements {
-                    // select要素を分割
-                    let split_messages = self.split_select_message(message)?;
-                    for split_message in split_messages {
-                        let xcstring = self.convert_message(&split_message)?;
-                        xcstrings.strings.insert(split_message.key, xcstring);
-                    }
-                } else {
-                    // エラーを返す
-                    return Err(format!("Select elements are not supported by xcstrings. Found in key: '{}'. Consider enabling split_select_elements option.", message.key));
-                }
-            } else {
-                // 通常処理
-                let xcstring = self.convert_message(message)?;
-                xcstrings.strings.insert(message.key.clone(), xcstring);
+            // select要素と序数を分割
+            for split_message in self.split_message(&message)? {
+                let xcstring = self.convert_message(&split_message)?;
+                xcstrings.strings.insert(split_message.key, xcstring);
             }
         }
         
@@ -68,25 +71,25 @@ impl XCStringConverter {
         let processed_messages: Result<Vec<_>, String> = messages
             .into_par_iter()
             .map(|message| {
+                let message = match self.with_source_language(&message)? {
+                    Some(message) => message,
+                    None => return Ok(Vec::new()),
+                };
                 // 変数の一貫性をチェック
+                self.validate_states(&message)?;
+                self.check_placeholders(&message)?;
+                self.check_select_cases(&message)?;
                 self.validate_variable_consistency(&message)?;
+                self.validate_tag_consistency(&message)?;
                 
-                if self.has_select_elements(&message) {
-                    if self.converter_options.split_select_elements {
-                        // select要素を分割
-                        let split_messages = self.split_select_message(&message)?;
-                        Ok(split_messages.into_iter().map(|split_message| {
-                            let xcstring = self.convert_message(&split_message)?;
-                            Ok((split_message.key, xcstring))
-                        }).collect::<Result<Vec<_>, String>>()?)
-                    } else {
-                        Err(format!("Select elements are not supported by xcstrings. Found in key: '{}'. Consider enabling split_select_elements option.", message.key))
-                    }
-                } else {
-                    // 通常処理
-                    let xcstring = self.convert_message(&message)?;
-                    Ok(vec![(message.key, xcstring)])
-                }
+                // select要素と序数を分割
+                self.split_message(&message)?
+                    .into_iter()
+                    .map(|split_message| {
+                        let xcstring = self.convert_message(&split_message)?;
+                        Ok((split_message.key, xcstring))
+                    })
+                    .collect::<Result<Vec<_>, String>>()
             })
             .collect();
         
@@ -108,17 +111,214 @@ impl XCStringConverter {
         Ok(xcstrings)
     }
 
+    /// Runs the lint passes over `messages` without converting them.
+    /// Messages that fail to parse are skipped here; conversion reports them.
+    pub fn validate(&self, messages: &[models::LocalizableICUMessage]) -> Vec<models::Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for message in messages {
+            diagnostics.extend(self.source_language_diagnostic(message));
+            if let Some(state) = &message.extraction_state {
+                if state.parse::<xcstrings::ExtractionState>().is_err() {
+                    diagnostics.push(models::Diagnostic::key_error(
+                        &message.key,
+                        models::DiagnosticKind::UnknownExtractionState { state: state.clone() },
+                    ));
+                }
+            }
+            for (locale, msg_value) in &message.messages {
+                let variation_states = msg_value.variation_states.iter().flat_map(|arguments| arguments.values());
+                for state in msg_value.state.iter().chain(variation_states.flat_map(|cases| cases.values())) {
+                    if state.parse::<xcstrings::LocalizationState>().is_err() {
+                        diagnostics.push(models::Diagnostic::error(
+                            &message.key,
+                            locale,
+                            models::DiagnosticKind::UnknownLocalizationState { state: state.clone() },
+                        ));
+                    }
+                }
+                let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
+                if let Ok(parsed) = parser.parse() {
+                    validation::validate_plural_categories(&message.key, locale, &parsed, &mut diagnostics);
+                    validation::validate_plural_offsets(
+                        &message.key,
+                        locale,
+                        &parsed,
+                        self.converter_options.plural_offset_handling,
+                        &mut diagnostics,
+                    );
+                    validation::validate_exact_cases(
+                        &message.key,
+                        locale,
+                        &parsed,
+                        self.converter_options.exact_match_handling,
+                        &mut diagnostics,
+                    );
+                    validation::validate_ordinals(
+                        &message.key,
+                        locale,
+                        &parsed,
+                        self.converter_options.ordinal_handling,
+                        &mut diagnostics,
+                    );
+                    validation::validate_printf_sequences(&message.key, locale, &parsed, &mut diagnostics);
+                    validation::validate_tags(
+                        &message.key,
+                        locale,
+                        &parsed,
+                        self.converter_options.markdown_tags,
+                        &mut diagnostics,
+                    );
+                }
+            }
+            self.validate_placeholder_declarations(message, &mut diagnostics);
+            self.validate_select_cases(message, &mut diagnostics);
+        }
+
+        diagnostics
+    }
+
+    /// Reports a key without a localization for the source language: a warning
+    /// when `missing_source_handling` skips or fills it, an error otherwise.
+    fn source_language_diagnostic(&self, message: &models::LocalizableICUMessage) -> Option<models::Diagnostic> {
+        if message.messages.contains_key(&self.source_language) {
+            return None;
+        }
+        let fallback_language = self.converter_options.fallback_language.as_deref();
+        let kind = |detail: String| models::DiagnosticKind::MissingSourceLanguage {
+            source_language: self.source_language.clone(),
+            detail,
+        };
+        let diagnostic = match (self.converter_options.missing_source_handling, fallback_language) {
+            (MissingSourceHandling::Error, _) => models::Diagnostic::key_error(
+                &message.key,
+                kind("Xcode shows such keys as broken; add it, or skip or fill these keys with the missing source handling".to_string()),
+            ),
+            (MissingSourceHandling::Skip, _) => {
+                models::Diagnostic::key_warning(&message.key, kind("the key is left out".to_string()))
+            }
+            (MissingSourceHandling::Fallback, Some(fallback_language)) if message.messages.contains_key(fallback_language) => {
+                models::Diagnostic::key_warning(
+                    &message.key,
+                    kind(format!("'{}' is copied in its place and marked needs_review", fallback_language)),
+                )
+            }
+            (MissingSourceHandling::Fallback, Some(fallback_language)) => models::Diagnostic::key_error(
+                &message.key,
+                kind(format!("the fallback language '{}' is missing too", fallback_language)),
+            ),
+            (MissingSourceHandling::Fallback, None) => {
+                models::Diagnostic::key_error(&message.key, kind("no fallback language is set".to_string()))
+            }
+        };
+        Some(diagnostic)
+    }
+
+    /// Applies `missing_source_handling` to `message`: `None` when the key is
+    /// skipped, otherwise the message with its source localization. This runs
+    /// before splitting, which keeps every locale, so split keys get it too.
+    fn with_source_language<'m>(
+        &self,
+        message: &'m models::LocalizableICUMessage,
+    ) -> Result<Option<Cow<'m, models::LocalizableICUMessage>>, String> {
+        let diagnostic = match self.source_language_diagnostic(message) {
+            Some(diagnostic) => diagnostic,
+            None => return Ok(Some(Cow::Borrowed(message))),
+        };
+        if diagnostic.severity == models::Severity::Error {
+            return Err(diagnostic.to_string());
+        }
+        let fallback = self
+            .converter_options
+            .fallback_language
+            .as_ref()
+            .and_then(|fallback_language| message.messages.get(fallback_language));
+        match (self.converter_options.missing_source_handling, fallback) {
+            (MissingSourceHandling::Fallback, Some(fallback)) => {
+                // ソース言語を先頭に置き、コピーした訳はレビュー待ちにする
+                let mut messages = LinkedHashMap::with_capacity(message.messages.len() + 1);
+                messages.insert(
+                    self.source_language.clone(),
+                    models::LocalizableICUMessageValue {
+                        value: fallback.value.clone(),
+                        state: Some("needs_review".to_string()),
+                        variation_states: None,
+                    },
+                );
+                for (locale, msg_value) in &message.messages {
+                    messages.insert(locale.clone(), msg_value.clone());
+                }
+                Ok(Some(Cow::Owned(models::LocalizableICUMessage {
+                    messages,
+                    ..message.clone()
+                })))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Unknown states are rejected rather than written as `translated`, since a
+    /// typo would otherwise mark unreviewed text as done.
+    fn validate_states(&self, message: &models::LocalizableICUMessage) -> Result<(), String> {
+        if let Some(state) = &message.extraction_state {
+            state
+                .parse::<xcstrings::ExtractionState>()
+                .map_err(|e| format!("Key '{}': {}", message.key, e))?;
+        }
+        for (locale, msg_value) in &message.messages {
+            self.localization_states(msg_value)
+                .map_err(|e| format!("Key '{}', language '{}': {}", message.key, locale, e))?;
+        }
+        Ok(())
+    }
+
+    /// Resolves the states of one locale's message: the converter default, then
+    /// the message's `state`, then its `variationStates`.
+    fn localization_states(
+        &self,
+        message: &models::LocalizableICUMessageValue,
+    ) -> Result<LocalizationStates, String> {
+        let mut states = LocalizationStates {
+            message: match &message.state {
+                Some(state) => state.parse()?,
+                None => self.converter_options.localization_state.clone(),
+            },
+            variations: LinkedHashMap::new(),
+        };
+        for (argument, cases) in message.variation_states.iter().flatten() {
+            let mut case_states = LinkedHashMap::with_capacity(cases.len());
+            for (case, state) in cases {
+                let state = state
+                    .parse()
+                    .map_err(|e| format!("case '{}' of '{}': {}", case, argument, e))?;
+                case_states.insert(case.clone(), state);
+            }
+            states.variations.insert(argument.clone(), case_states);
+        }
+        Ok(states)
+    }
+
+    /// Every locale has to use the same arguments in the same way: a plural in
+    /// one locale and a plain argument in another would get different format
+    /// specifiers for the same position. The same goes for number styles, where
+    /// `{ratio, number, percent}` becomes `%@` but `{ratio, number}` becomes `%lld`.
     fn validate_variable_consistency(&self, message: &models::LocalizableICUMessage) -> Result<(), String> {
-        let mut reference_variables: Option<std::collections::HashSet<String>> = None;
+        let declared_specifiers = declared_specifiers(message);
+        let mut reference_variables: Option<(&String, ArgumentKinds, XCStringFormatter)> = None;
         
         for (locale, msg_value) in &message.messages {
-            let variables = self.extract_variables(&msg_value.value)?;
+            let mut variables = self.extract_variables(&msg_value.value)?;
+            let specifiers = self.resolve_specifiers(&msg_value.value, &declared_specifiers)?;
+            // ロケールごとにデバイスで分けるかどうかは自由
+            if let Some(device_argument) = &self.converter_options.device_argument {
+                variables.remove(device_argument);
+            }
             
             match &reference_variables {
                 None => {
-                    reference_variables = Some(variables);
+                    reference_variables = Some((locale, variables, specifiers));
                 }
-                Some(ref_vars) => {
+                Some((ref_locale, ref_vars, ref_specifiers)) => {
                     if variables.len() != ref_vars.len() {
                         return Err(format!(
                             "Variable count mismatch in key '{}'. Language '{}' has {} variables, but expected {}",
@@ -126,17 +326,41 @@ impl XCStringConverter {
                         ));
                     }
                     
-                    for var in &variables {
-                        if !ref_vars.contains(var) {
-                            return Err(format!(
-                                "Variable name mismatch in key '{}'. Language '{}' contains variable '{}' which is not found in other languages. Expected variables: {:?}",
-                                message.key, locale, var, ref_vars.iter().collect::<Vec<_>>()
-                            ));
+                    for (var, kinds) in &variables {
+                        match ref_vars.get(var) {
+                            None => {
+                                return Err(format!(
+                                    "Variable name mismatch in key '{}'. Language '{}' contains variable '{}' which is not found in other languages. Expected variables: {:?}",
+                                    message.key, locale, var, ref_vars.keys().collect::<Vec<_>>()
+                                ));
+                            }
+                            Some(ref_kinds) if ref_kinds != kinds => {
+                                return Err(format!(
+                                    "Variable kind mismatch in key '{}'. Language '{}' uses '{}' as {}, but '{}' uses it as {}",
+                                    message.key,
+                                    ref_locale,
+                                    var,
+                                    describe_kinds(ref_kinds),
+                                    locale,
+                                    describe_kinds(kinds)
+                                ));
+                            }
+                            Some(_) => {}
+                        }
+                        if let (Some(ref_specifier), Some(specifier)) =
+                            (ref_specifiers.specifier_for(var), specifiers.specifier_for(var))
+                        {
+                            if ref_specifier != specifier {
+                                return Err(format!(
+                                    "Format specifier mismatch in key '{}'. Language '{}' formats '{}' as %{}, but '{}' formats it as %{}; use the same number style or declare the placeholder",
+                                    message.key, ref_locale, var, ref_specifier, locale, specifier
+                                ));
+                            }
                         }
                     }
                     
-                    for var in ref_vars {
-                        if !variables.contains(var) {
+                    for var in ref_vars.keys() {
+                        if !variables.contains_key(var) {
                             return Err(format!(
                                 "Missing variable in key '{}'. Language '{}' is missing variable '{}' which exists in other languages",
                                 message.key, locale, var
@@ -150,51 +374,143 @@ impl XCStringConverter {
         Ok(())
     }
     
-    fn extract_variables(&self, message_value: &str) -> Result<std::collections::HashSet<String>, String> {
-        let mut variables = std::collections::HashSet::new();
+    /// Every locale has to use the same tags, since they turn into markup the
+    /// app renders (or into the `<link>` URL argument).
+    fn validate_tag_consistency(&self, message: &models::LocalizableICUMessage) -> Result<(), String> {
+        let mut reference_tags: Option<(&String, std::collections::BTreeSet<String>)> = None;
+
+        for (locale, msg_value) in &message.messages {
+            let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
+            let parsed = match parser.parse() {
+                Ok(parsed) => parsed,
+                Err(e) => return Err(format!("Failed to parse message '{}': {:?}", msg_value.value, e)),
+            };
+            let mut tags = std::collections::BTreeSet::new();
+            collect_tags(&parsed, &mut tags);
+
+            match &reference_tags {
+                None => reference_tags = Some((locale, tags)),
+                Some((reference_locale, reference)) => {
+                    if tags != *reference {
+                        return Err(format!(
+                            "Tag mismatch in key '{}'. Language '{}' uses tags {:?}, but '{}' uses {:?}",
+                            message.key, locale, tags, reference_locale, reference
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Assigns positions for one locale's message on its own, recording the
+    /// specifier each argument resolves to.
+    fn resolve_specifiers(
+        &self,
+        message_value: &str,
+        declared_specifiers: &HashMap<String, String>,
+    ) -> Result<XCStringFormatter, String> {
+        let mut parser = icu_messageformat_parser::Parser::new(message_value, &self.parser_options);
+        let parsed = match parser.parse() {
+            Ok(parsed) => parsed,
+            Err(e) => return Err(format!("Failed to parse message '{}': {:?}", message_value, e)),
+        };
+        let mut formatter = XCStringFormatter::new(FormatterMode::StringUnit)
+            .with_markdown_tags(self.converter_options.markdown_tags)
+            .with_device_argument(self.converter_options.device_argument.clone())
+            .with_declared_specifiers(declared_specifiers.clone());
+        formatter.assign_positions(&parsed)?;
+        Ok(formatter)
+    }
+
+    fn extract_variables(&self, message_value: &str) -> Result<ArgumentKinds, String> {
+        let mut variables = ArgumentKinds::new();
         let mut parser = icu_messageformat_parser::Parser::new(message_value, &self.parser_options);
         
         match parser.parse() {
             Ok(parsed) => {
-                self.collect_variables_from_ast(&parsed, &mut variables);
+                self.collect_variables_from_ast(&parsed, None, &mut variables);
                 Ok(variables)
             }
             Err(e) => Err(format!("Failed to parse message '{}': {:?}", message_value, e))
         }
     }
     
-    fn collect_variables_from_ast(&self, elements: &[icu_messageformat_parser::AstElement], variables: &mut std::collections::HashSet<String>) {
+    /// Records how each argument is used. Inside a plural's branches its own
+    /// argument is the plural's number, so it isn't recorded again there.
+    fn collect_variables_from_ast(
+        &self,
+        elements: &[icu_messageformat_parser::AstElement],
+        plural_argument: Option<&str>,
+        variables: &mut ArgumentKinds,
+    ) {
         for element in elements {
-            match element {
-                icu_messageformat_parser::AstElement::Argument { value, .. } |
-                icu_messageformat_parser::AstElement::Number { value, .. } |
-                icu_messageformat_parser::AstElement::Date { value, .. } => {
-                    variables.insert(value.clone());
-                }
+            let (value, kind) = match element {
+                icu_messageformat_parser::AstElement::Argument { value, .. } => (value, ArgumentUsage::Plain),
+                icu_messageformat_parser::AstElement::Number { value, .. } => (value, ArgumentUsage::Number),
+                icu_messageformat_parser::AstElement::Date { value, .. } => (value, ArgumentUsage::Date),
+                icu_messageformat_parser::AstElement::Time { value, .. } => (value, ArgumentUsage::Time),
                 icu_messageformat_parser::AstElement::Plural { value, options, .. } => {
-                    variables.insert(value.clone());
                     for (_, option) in &options.0 {
-                        self.collect_variables_from_ast(&option.value, variables);
+                        self.collect_variables_from_ast(&option.value, Some(value), variables);
                     }
+                    (value, ArgumentUsage::Plural)
                 }
                 icu_messageformat_parser::AstElement::Select { value, options, .. } => {
-                    variables.insert(value.clone());
                     for (_, option) in &options.0 {
-                        self.collect_variables_from_ast(&option.value, variables);
+                        self.collect_variables_from_ast(&option.value, plural_argument, variables);
                     }
+                    (value, ArgumentUsage::Select)
+                }
+                icu_messageformat_parser::AstElement::Tag { children, .. } => {
+                    self.collect_variables_from_ast(children, plural_argument, variables);
+                    continue;
                 }
-                _ => {}
+                _ => continue,
+            };
+            if plural_argument == Some(value.as_str()) && kind != ArgumentUsage::Plural {
+                continue;
             }
+            variables.entry(value.clone()).or_default().insert(kind);
         }
     }
 
     fn convert_message(&self, localizable_icu_message: &models::LocalizableICUMessage) -> Result<xcstrings::XCString, String> {
+        self.check_plural_offsets(localizable_icu_message)?;
+        let declared_specifiers = declared_specifiers(localizable_icu_message);
+        let source_formatter = self.source_formatter(&localizable_icu_message.messages, &declared_specifiers)?;
+        let legend = if self.converter_options.placeholder_legend {
+            let positioned = !self.has_unpositioned_plural(&localizable_icu_message.messages, &source_formatter);
+            source_formatter.placeholder_legend(positioned)
+        } else {
+            None
+        };
+        let descriptions = placeholder_descriptions(localizable_icu_message);
+        let is_comment_auto_generated = match (&localizable_icu_message.comment, &legend, &descriptions) {
+            (None, Some(_), None) => Some(true),
+            _ => None,
+        };
+        let comment = [localizable_icu_message.comment.clone(), legend, descriptions]
+            .into_iter()
+            .flatten()
+            .reduce(|comment, part| format!("{}\n{}", comment, part));
+
+        let extraction_state = match &localizable_icu_message.extraction_state {
+            Some(state) => state.parse()?,
+            None => self.converter_options.extraction_state.clone(),
+        };
+
         let mut xcstring = xcstrings::XCString {
-            extraction_state: xcstrings::ExtractionState::Manual,
+            comment,
+            extraction_state,
+            is_comment_auto_generated,
             localizations: LinkedHashMap::with_capacity(localizable_icu_message.messages.len()),
+            // Xcode only writes the flag for keys that shouldn't be translated
+            should_translate: localizable_icu_message.should_translate.filter(|should_translate| !should_translate),
         };
         
-        let localizations = self.format(&localizable_icu_message.messages)?;
+        let localizations = self.format(&localizable_icu_message.messages, &source_formatter, &declared_specifiers)?;
         for (locale, localization) in localizations {
             xcstring.localizations.insert(locale, localization);
         }
@@ -205,147 +521,1054 @@ impl XCStringConverter {
     fn format(
         &self,
         messages: &LinkedHashMap<String, models::LocalizableICUMessageValue>,
+        source_formatter: &XCStringFormatter,
+        declared_specifiers: &HashMap<String, String>,
     ) -> Result<LinkedHashMap<String, xcstrings::Localization>, String> {
         let mut result = LinkedHashMap::with_capacity(messages.len());
         
         for (locale, message) in messages.iter() {
-            let mut formatter = XCStringFormatter::new(FormatterMode::StringUnit);
+            let mut formatter = XCStringFormatter::with_positions(
+                FormatterMode::StringUnit,
+                source_formatter.argument_positions().clone(),
+                source_formatter.argument_specifiers().clone(),
+            )
+            .with_markdown_tags(self.converter_options.markdown_tags)
+            .with_device_argument(self.converter_options.device_argument.clone())
+            .with_declared_specifiers(declared_specifiers.clone());
             let mut parser = icu_messageformat_parser::Parser::new(&message.value, &self.parser_options);
             let parsed = match parser.parse() {
                 Ok(parsed) => parsed,
                 Err(e) => return Err(format!("Failed to parse message for locale '{}': {:?}", locale, e))
             };
             
-            let plural_and_selects: Vec<AstElement> = parsed
-                .iter()
-                .filter(|element| matches!(element, AstElement::Plural { .. } | AstElement::Select { .. }))
-                .cloned()
-                .collect();
-            
-            let substitution_builder = XCStringSubstitutionBuilder::new();
-            let substitutions = match substitution_builder.build(plural_and_selects) {
-                Ok(substitutions) => substitutions,
-                Err(e) => return Err(format!("Failed to build substitutions for locale '{}': {}", locale, e))
-            };
-            
-            // 効率的な文字列結合
-            let mut formatted_string = String::with_capacity(message.value.len());
-            for element in &parsed {
-                formatted_string.push_str(&formatter.format(element)?);
-            }
-            
-            result.insert(
-                locale.clone(),
-                xcstrings::Localization {
-                    string_unit: xcstrings::StringUnit {
-                        localization_state: match message.state.as_str() {
-                            "translated" => xcstrings::LocalizationState::Translated,
-                            "needs_review" => xcstrings::LocalizationState::NeedsReview,
-                            _ => xcstrings::LocalizationState::Translated,
-                        },
-                        value: formatted_string,
-                    },
-                    substitutions: if substitutions.is_empty() {
-                        None
+            let states = self.localization_states(message)?;
+
+            let localization = match (self.device_cases(&parsed), self.whole_message_plural(&parsed)) {
+                (None, Some(plural_argument)) => {
+                    let position = formatter.position_for(plural_argument, "lld")?;
+                    let specifier = formatter.specifier_for(plural_argument).unwrap_or("lld").to_string();
+                    // 引数が plural だけなら Xcode と同じく位置を省略する
+                    let placeholder = if formatter.argument_positions().len() == 1 {
+                        format!("%{}", specifier)
                     } else {
-                        Some(substitutions)
-                    },
-                },
-            );
+                        format!("%{}${}", position, specifier)
+                    };
+                    let previous_placeholder = formatter.set_plural_placeholder(placeholder);
+                    let (_, mut substitutions) = self.format_elements(locale, &parsed, &states, &mut formatter)?;
+                    formatter.set_plural_placeholder(previous_placeholder);
+
+                    let variations = match substitutions.remove(plural_argument) {
+                        Some(xcstrings::Substitution { variations: xcstrings::VariationType::Plural(variations), .. }) => {
+                            variations
+                        }
+                        _ => return Err(format!("Failed to build plural variations for locale '{}'", locale)),
+                    };
+                    xcstrings::Localization {
+                        string_unit: None,
+                        substitutions: None,
+                        variations: Some(xcstrings::LocalizationVariations::Plural(variations)),
+                    }
+                }
+                (None, None) => {
+                    let (formatted_string, substitutions) = self.format_elements(locale, &parsed, &states, &mut formatter)?;
+                    xcstrings::Localization {
+                        string_unit: Some(xcstrings::StringUnit {
+                            localization_state: states.message.clone(),
+                            value: formatted_string,
+                        }),
+                        substitutions: if substitutions.is_empty() {
+                            None
+                        } else {
+                            Some(substitutions)
+                        },
+                        variations: None,
+                    }
+                }
+                (Some((device_argument, cases)), _) => {
+                    let mut devices = LinkedHashMap::with_capacity(cases.len());
+                    let mut substitutions: LinkedHashMap<String, xcstrings::Substitution> = LinkedHashMap::new();
+                    for case_key in cases {
+                        if !xcstrings::DEVICE_VARIATIONS.contains(&case_key) {
+                            return Err(format!(
+                                "Device select '{}' for locale '{}' has the case '{}', which isn't a String Catalog device. Use one of: {}",
+                                device_argument,
+                                locale,
+                                case_key,
+                                xcstrings::DEVICE_VARIATIONS.join(", ")
+                            ));
+                        }
+                        let elements = replace_selects(parsed.clone(), &[(device_argument, case_key)], None);
+                        let (formatted_string, case_substitutions) =
+                            self.format_elements(locale, &elements, &states, &mut formatter)?;
+                        // デバイスのバリエーションは同じ substitutions を共有する
+                        for (name, substitution) in case_substitutions {
+                            match substitutions.get(&name) {
+                                Some(existing) if *existing != substitution => {
+                                    return Err(format!(
+                                        "'{}' differs between the device cases of '{}' for locale '{}', but String Catalogs share substitutions across device variations",
+                                        name, device_argument, locale
+                                    ));
+                                }
+                                Some(_) => {}
+                                None => {
+                                    substitutions.insert(name, substitution);
+                                }
+                            }
+                        }
+                        devices.insert(
+                            case_key.to_string(),
+                            xcstrings::VariationValue {
+                                string_unit: xcstrings::StringUnit {
+                                    localization_state: states.variation(device_argument, &[case_key]),
+                                    value: formatted_string,
+                                },
+                            },
+                        );
+                    }
+                    xcstrings::Localization {
+                        string_unit: None,
+                        substitutions: if substitutions.is_empty() {
+                            None
+                        } else {
+                            Some(substitutions)
+                        },
+                        variations: Some(xcstrings::LocalizationVariations::Device(devices)),
+                    }
+                }
+            };
+
+            result.insert(locale.clone(), localization);
         }
         
         Ok(result)
     }
 
+    /// Formats `elements` as a string unit value and the substitutions for its
+    /// top-level plurals and selects.
+    fn format_elements(
+        &self,
+        locale: &str,
+        elements: &[AstElement],
+        states: &LocalizationStates,
+        formatter: &mut XCStringFormatter,
+    ) -> Result<(String, LinkedHashMap<String, xcstrings::Substitution>), String> {
+        let plural_and_selects: Vec<AstElement> = elements
+            .iter()
+            .filter(|element| matches!(element, AstElement::Plural { .. } | AstElement::Select { .. }))
+            .cloned()
+            .collect();
+
+        let substitution_builder = XCStringSubstitutionBuilder::with_states(states.clone());
+        let substitutions = match substitution_builder.build(plural_and_selects, formatter) {
+            Ok(substitutions) => substitutions,
+            Err(e) => return Err(format!("Failed to build substitutions for locale '{}': {}", locale, e))
+        };
+
+        let formatted_string = formatter.format_elements(elements)?;
+        Ok((formatted_string, substitutions))
+    }
+
+    /// The plural's argument when `elements` is nothing but one plural, which
+    /// becomes plural variations of the whole localization when
+    /// `whole_message_plurals` is on. Plurals with nested plurals keep the
+    /// substitution form, since the nested ones become substitutions too.
+    fn whole_message_plural<'a>(&self, elements: &'a [AstElement]) -> Option<&'a str> {
+        match elements {
+            [AstElement::Plural { value, .. }]
+                if self.converter_options.whole_message_plurals
+                    && nested_plurals(elements).is_ok_and(|nested| nested.is_empty()) =>
+            {
+                Some(value.as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// The device select argument and its cases in order of appearance, when
+    /// `elements` has a device select that selects can be hoisted from.
+    fn device_cases<'a>(&self, elements: &'a [AstElement]) -> Option<(&'a str, Vec<&'a str>)> {
+        let device_argument = self.converter_options.device_argument.as_deref()?;
+        let mut selects = Vec::new();
+        collect_hoistable_selects(elements, None, &mut selects);
+        selects.into_iter().find(|(argument, _)| *argument == device_argument)
+    }
+
+    /// Assigns argument positions once per key from the source language message,
+    /// so that `%1$@` refers to the same argument in every localization even when
+    /// translators reorder placeholders. Falls back to the first locale when the
+    /// source language is missing. Arguments only other locales use come after
+    /// the source's, so the legend covers them too, as in an `_eqN` key whose
+    /// source case has no argument while another locale keeps the plural.
+    fn source_formatter(
+        &self,
+        messages: &LinkedHashMap<String, models::LocalizableICUMessageValue>,
+        declared_specifiers: &HashMap<String, String>,
+    ) -> Result<XCStringFormatter, String> {
+        let source = messages
+            .iter()
+            .find(|(locale, _)| **locale == self.source_language)
+            .or_else(|| messages.iter().next());
+        let (locale, message) = match source {
+            Some(source) => source,
+            None => {
+                return Ok(XCStringFormatter::new(FormatterMode::StringUnit)
+                    .with_declared_specifiers(declared_specifiers.clone()))
+            }
+        };
+
+        let mut parser = icu_messageformat_parser::Parser::new(&message.value, &self.parser_options);
+        let parsed = match parser.parse() {
+            Ok(parsed) => parsed,
+            Err(e) => return Err(format!("Failed to parse message for locale '{}': {:?}", locale, e))
+        };
+
+        let mut formatter = XCStringFormatter::new(FormatterMode::StringUnit)
+            .with_markdown_tags(self.converter_options.markdown_tags)
+            .with_device_argument(self.converter_options.device_argument.clone())
+            .with_declared_specifiers(declared_specifiers.clone());
+        formatter.assign_positions(&parsed)?;
+        for (other_locale, other_message) in messages {
+            if other_locale == locale {
+                continue;
+            }
+            let mut parser = icu_messageformat_parser::Parser::new(&other_message.value, &self.parser_options);
+            if let Ok(parsed) = parser.parse() {
+                formatter.assign_positions(&parsed)?;
+            }
+        }
+        Ok(formatter)
+    }
+
+    /// Whether a locale writes the key's only argument without a position, as
+    /// `format` does for the `%lld` of whole-message plural variations. The
+    /// argument appears nowhere else with a position then, so the legend leaves
+    /// it out too.
+    fn has_unpositioned_plural(
+        &self,
+        messages: &LinkedHashMap<String, models::LocalizableICUMessageValue>,
+        source_formatter: &XCStringFormatter,
+    ) -> bool {
+        source_formatter.argument_positions().len() == 1
+            && messages.values().any(|message| {
+                let mut parser = icu_messageformat_parser::Parser::new(&message.value, &self.parser_options);
+                parser.parse().is_ok_and(|parsed| {
+                    self.device_cases(&parsed).is_none() && self.whole_message_plural(&parsed).is_some()
+                })
+            })
+    }
+
+    /// Fails with the first plural offset that can't be converted, naming the key
+    /// and locale.
+    fn check_plural_offsets(&self, message: &models::LocalizableICUMessage) -> Result<(), String> {
+        let mut diagnostics = Vec::new();
+        for (locale, msg_value) in &message.messages {
+            let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
+            if let Ok(parsed) = parser.parse() {
+                validation::validate_plural_offsets(
+                    &message.key,
+                    locale,
+                    &parsed,
+                    self.converter_options.plural_offset_handling,
+                    &mut diagnostics,
+                );
+            }
+        }
+        match diagnostics.first() {
+            Some(diagnostic) => Err(diagnostic.to_string()),
+            None => Ok(()),
+        }
+    }
+
+    /// Reports placeholder declarations that are unknown, unused or don't fit how
+    /// the messages use their arguments. Messages that fail to parse are skipped.
+    fn validate_placeholder_declarations(
+        &self,
+        message: &models::LocalizableICUMessage,
+        diagnostics: &mut Vec<models::Diagnostic>,
+    ) {
+        let placeholders = match &message.placeholders {
+            Some(placeholders) => placeholders,
+            None => return,
+        };
+        let mut used_arguments = std::collections::BTreeSet::new();
+        for (locale, msg_value) in &message.messages {
+            let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
+            if let Ok(parsed) = parser.parse() {
+                validation::validate_placeholder_types(&message.key, locale, &parsed, placeholders, diagnostics);
+                let mut usages = Vec::new();
+                validation::argument_usages(&parsed, &mut usages);
+                used_arguments.extend(usages.into_iter().map(|(argument, _)| argument));
+            }
+        }
+        validation::validate_placeholder_declarations(&message.key, placeholders, &used_arguments, diagnostics);
+    }
+
+    /// Fails with the first placeholder declaration error, before selects are
+    /// split off.
+    fn check_placeholders(&self, message: &models::LocalizableICUMessage) -> Result<(), String> {
+        let mut diagnostics = Vec::new();
+        self.validate_placeholder_declarations(message, &mut diagnostics);
+        match diagnostics.iter().find(|diagnostic| diagnostic.severity == models::Severity::Error) {
+            Some(diagnostic) => Err(diagnostic.to_string()),
+            None => Ok(()),
+        }
+    }
+
+    /// Reports select cases that only some locales have. Only applies when
+    /// selects are split, since the split keys are what the gaps affect.
+    fn validate_select_cases(
+        &self,
+        message: &models::LocalizableICUMessage,
+        diagnostics: &mut Vec<models::Diagnostic>,
+    ) {
+        if !self.converter_options.split_select_elements {
+            return;
+        }
+        let mut locale_cases = Vec::with_capacity(message.messages.len());
+        for (locale, msg_value) in &message.messages {
+            let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
+            if let Ok(parsed) = parser.parse() {
+                let mut cases = Vec::new();
+                validation::select_cases(&parsed, self.converter_options.device_argument.as_deref(), &mut cases);
+                locale_cases.push((locale.clone(), cases));
+            }
+        }
+        // ソース言語がなければ最初のロケールを基準にする
+        let reference_locale = if message.messages.contains_key(&self.source_language) {
+            self.source_language.as_str()
+        } else {
+            match message.messages.keys().next() {
+                Some(locale) => locale.as_str(),
+                None => return,
+            }
+        };
+        validation::validate_select_cases(
+            &message.key,
+            reference_locale,
+            &locale_cases,
+            self.converter_options.strict_select_cases,
+            diagnostics,
+        );
+    }
+
+    /// Fails with the first select case a locale lacks when select cases are
+    /// strict.
+    fn check_select_cases(&self, message: &models::LocalizableICUMessage) -> Result<(), String> {
+        let mut diagnostics = Vec::new();
+        self.validate_select_cases(message, &mut diagnostics);
+        match diagnostics.iter().find(|diagnostic| diagnostic.severity == models::Severity::Error) {
+            Some(diagnostic) => Err(diagnostic.to_string()),
+            None => Ok(()),
+        }
+    }
+
     fn has_select_elements(&self, message: &models::LocalizableICUMessage) -> bool {
         message.messages.values().any(|msg_value| {
             let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
             if let Ok(parsed) = parser.parse() {
-                parsed.iter().any(|element| matches!(element, AstElement::Select { .. }))
+                let mut selects = Vec::new();
+                collect_hoistable_selects(&parsed, self.converter_options.device_argument.as_deref(), &mut selects);
+                !selects.is_empty()
             } else {
                 false
             }
         })
     }
 
-    fn split_select_message(&self, message: &models::LocalizableICUMessage) -> Result<Vec<models::LocalizableICUMessage>, String> {
-        let mut split_messages = Vec::new();
-        
-        let first_message = match message.messages.values().next() {
-            Some(msg) => msg,
-            None => return Err(format!("No messages found for key '{}'", message.key))
+    /// Splits `message` into the messages that become keys: one per ordinal
+    /// category when ordinals are split, one per exact plural case when those
+    /// are split, then one per select case.
+    fn split_message(&self, message: &models::LocalizableICUMessage) -> Result<Vec<models::LocalizableICUMessage>, String> {
+        let ordinal_messages = match self.converter_options.ordinal_handling {
+            OrdinalHandling::Split => self.split_ordinal_message(message)?,
+            OrdinalHandling::Cardinal => vec![message.clone()],
         };
         
-        let mut parser = icu_messageformat_parser::Parser::new(&first_message.value, &self.parser_options);
-        if let Ok(parsed) = parser.parse() {
-            if let Some(select_element) = parsed.iter().find(|element| matches!(element, AstElement::Select { .. })) {
-                if let AstElement::Select { value: _, span: _, options } = select_element {
-                    for (case_key, _) in &options.0 {
-                        let new_key = format!("{}_{}", message.key, case_key);
-                        let mut new_messages = LinkedHashMap::with_capacity(message.messages.len());
-                        
-                        for (locale, msg_value) in &message.messages {
-                            let new_value = self.replace_select_with_case(&msg_value.value, case_key);
-                            new_messages.insert(locale.clone(), models::LocalizableICUMessageValue {
-                                value: new_value,
-                                state: msg_value.state.clone(),
-                            });
-                        }
-                        
-                        split_messages.push(models::LocalizableICUMessage {
-                            key: new_key,
-                            messages: new_messages,
-                            comment: message.comment.clone(),
-                        });
-                    }
-                }
+        let mut exact_messages = Vec::with_capacity(ordinal_messages.len());
+        for ordinal_message in ordinal_messages {
+            match self.converter_options.exact_match_handling {
+                ExactMatchHandling::Error => exact_messages.push(ordinal_message),
+                ExactMatchHandling::SplitKeys => exact_messages.extend(self.split_exact_message(&ordinal_message)?),
+                ExactMatchHandling::Fold => exact_messages.push(self.fold_exact_message(&ordinal_message)),
             }
         }
         
-        if split_messages.is_empty() {
-            Ok(vec![message.clone()])
-        } else {
-            Ok(split_messages)
+        let mut split_messages = Vec::with_capacity(exact_messages.len());
+        for exact_message in exact_messages {
+            if !self.has_select_elements(&exact_message) {
+                split_messages.push(exact_message);
+            } else if self.converter_options.split_select_elements {
+                // select要素を分割
+                split_messages.extend(self.split_select_message(&exact_message)?);
+            } else {
+                return Err(format!("Select elements are not supported by xcstrings. Found in key: '{}'. Consider enabling split_select_elements option.", message.key));
+            }
         }
+        Ok(split_messages)
     }
 
-    fn replace_select_with_case(&self, original_value: &str, case_key: &str) -> String {
-        let mut parser = icu_messageformat_parser::Parser::new(original_value, &self.parser_options);
-        if let Ok(parsed) = parser.parse() {
-            let mut formatter = XCStringFormatter::new(FormatterMode::StringUnit);
-            let mut result = String::with_capacity(original_value.len());
-            
-            for element in &parsed {
-                match element {
-                    AstElement::Select { options, .. } => {
-                        let case_option = options.0.iter().find(|(key, _)| *key == case_key);
-                        if let Some((_, option)) = case_option {
-                            for e in &option.value {
-                                result.push_str(&formatter.format(e).unwrap_or_default());
-                            }
-                        } else {
-                            let other_option = options.0.iter().find(|(key, _)| *key == "other");
-                            if let Some((_, option)) = other_option {
-                                for e in &option.value {
-                                    result.push_str(&formatter.format(e).unwrap_or_default());
-                                }
-                            }
-                        }
-                    },
-                    _ => {
-                        if let Ok(formatted) = formatter.format(element) {
-                            result.push_str(&formatted);
-                        }
+    /// Every combination of one case per dimension, in order. Fails when there
+    /// are more combinations than `max_select_combinations` allows.
+    fn case_combinations<'a>(
+        &self,
+        key: &str,
+        kind: &str,
+        dimensions: &'a [(&'a str, Vec<&'a str>)],
+    ) -> Result<Vec<Vec<(&'a str, &'a str)>>, String> {
+        let combination_count = dimensions
+            .iter()
+            .try_fold(1usize, |count, (_, cases)| count.checked_mul(cases.len()))
+            .filter(|count| *count <= self.converter_options.max_select_combinations);
+        if combination_count.is_none() {
+            let description: Vec<String> = dimensions
+                .iter()
+                .map(|(argument, cases)| format!("'{}' ({} cases)", argument, cases.len()))
+                .collect();
+            return Err(format!(
+                "Splitting key '{}' on {} {} would generate more than {} keys. Reduce the number of cases or raise max_select_combinations.",
+                key,
+                kind,
+                description.join(", "),
+                self.converter_options.max_select_combinations
+            ));
+        }
+        
+        let mut combinations: Vec<Vec<(&str, &str)>> = vec![Vec::new()];
+        for (argument, cases) in dimensions {
+            combinations = combinations
+                .iter()
+                .flat_map(|combination| {
+                    cases.iter().map(move |case_key| {
+                        let mut combination = combination.clone();
+                        combination.push((*argument, *case_key));
+                        combination
+                    })
+                })
+                .collect();
+        }
+        Ok(combinations)
+    }
+
+    /// Splits `message` into one key per `selectordinal` case, e.g. `key_one`,
+    /// `key_two`, `key_few` and `key_other`. Cases are merged across locales so
+    /// every locale's categories get a key; a locale without a case uses `other`.
+    fn split_ordinal_message(&self, message: &models::LocalizableICUMessage) -> Result<Vec<models::LocalizableICUMessage>, String> {
+        let mut ordinals: Vec<(String, Vec<String>)> = Vec::new();
+        for msg_value in message.messages.values() {
+            let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
+            if let Ok(parsed) = parser.parse() {
+                collect_ordinals(&parsed, &mut ordinals);
+            }
+        }
+        if ordinals.is_empty() {
+            return Ok(vec![message.clone()]);
+        }
+        
+        let dimensions: Vec<(&str, Vec<&str>)> = ordinals
+            .iter()
+            .map(|(argument, cases)| (argument.as_str(), cases.iter().map(String::as_str).collect()))
+            .collect();
+        let combinations = self.case_combinations(&message.key, "ordinals", &dimensions)?;
+        
+        let mut split_messages = Vec::with_capacity(combinations.len());
+        for combination in &combinations {
+            let mut new_key = message.key.clone();
+            for (_, case_key) in combination {
+                new_key.push('_');
+                match case_key.strip_prefix('=') {
+                    Some(number) => {
+                        new_key.push_str("eq");
+                        new_key.push_str(number);
                     }
+                    None => new_key.push_str(case_key),
                 }
             }
-            result
-        } else {
-            original_value.to_string()
+            let mut new_messages = LinkedHashMap::with_capacity(message.messages.len());
+            
+            for (locale, msg_value) in &message.messages {
+                let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
+                let new_value = match parser.parse() {
+                    Ok(parsed) => icu_printer::print(&replace_ordinals(parsed, combination)),
+                    Err(_) => msg_value.value.clone(),
+                };
+                new_messages.insert(locale.clone(), split_message_value(msg_value, new_value, combination));
+            }
+            
+            split_messages.push(models::LocalizableICUMessage {
+                key: new_key,
+                messages: new_messages,
+                comment: message.comment.clone(),
+                extraction_state: message.extraction_state.clone(),
+                should_translate: message.should_translate,
+                placeholders: message.placeholders.clone(),
+            });
         }
+        
+        Ok(split_messages)
     }
 
-}
+    /// Moves exact `=N` plural cases without a variation of their own to separate
+    /// keys with an `_eqN` suffix. The original key keeps the other cases, and a
+    /// locale without a given `=N` case uses them for that key as well.
+    fn split_exact_message(&self, message: &models::LocalizableICUMessage) -> Result<Vec<models::LocalizableICUMessage>, String> {
+        let mut exact_cases: Vec<(String, Vec<String>)> = Vec::new();
+        for msg_value in message.messages.values() {
+            let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
+            if let Ok(parsed) = parser.parse() {
+                collect_exact_cases(&parsed, &mut exact_cases);
+            }
+        }
+        if exact_cases.is_empty() {
+            return Ok(vec![message.clone()]);
+        }
+        
+        // 空文字列は =N 以外のケースを残した元のキーを表す
+        let dimensions: Vec<(&str, Vec<&str>)> = exact_cases
+            .iter()
+            .map(|(argument, cases)| {
+                let mut choices = vec![""];
+                choices.extend(cases.iter().map(String::as_str));
+                (argument.as_str(), choices)
+            })
+            .collect();
+        let combinations = self.case_combinations(&message.key, "exact plural cases", &dimensions)?;
+        
+        let mut split_messages = Vec::with_capacity(combinations.len());
+        for combination in &combinations {
+            let mut new_key = message.key.clone();
+            for (_, case_key) in combination {
+                if let Some(number) = case_key.strip_prefix('=') {
+                    new_key.push_str("_eq");
+                    new_key.push_str(number);
+                }
+            }
+            let mut new_messages = LinkedHashMap::with_capacity(message.messages.len());
+            
+            for (locale, msg_value) in &message.messages {
+                let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
+                let new_value = match parser.parse() {
+                    Ok(parsed) => icu_printer::print(&replace_exact_cases(parsed, combination)),
+                    Err(_) => msg_value.value.clone(),
+                };
+                new_messages.insert(locale.clone(), split_message_value(msg_value, new_value, combination));
+            }
+            
+            split_messages.push(models::LocalizableICUMessage {
+                key: new_key,
+                messages: new_messages,
+                comment: message.comment.clone(),
+                extraction_state: message.extraction_state.clone(),
+                should_translate: message.should_translate,
+                placeholders: message.placeholders.clone(),
+            });
+        }
+        
+        Ok(split_messages)
+    }
+
+    /// Folds exact `=N` plural cases without a variation of their own into the
+    /// category each locale selects for N. A case whose category already exists
+    /// is dropped.
+    fn fold_exact_message(&self, message: &models::LocalizableICUMessage) -> models::LocalizableICUMessage {
+        let mut folded = message.clone();
+        for (locale, msg_value) in folded.messages.iter_mut() {
+            let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
+            if let Ok(parsed) = parser.parse() {
+                let mut exact_cases = Vec::new();
+                collect_exact_cases(&parsed, &mut exact_cases);
+                if !exact_cases.is_empty() {
+                    msg_value.value = icu_printer::print(&fold_exact_cases(parsed, locale));
+                }
+            }
+        }
+        folded
+    }
+
+    /// Splits `message` into one key per combination of select cases. The cases
+    /// are merged across locales, so a case only some locales have still gets a
+    /// key; the others use `other` for it.
+    fn split_select_message(&self, message: &models::LocalizableICUMessage) -> Result<Vec<models::LocalizableICUMessage>, String> {
+        if message.messages.is_empty() {
+            return Err(format!("No messages found for key '{}'", message.key));
+        }
+        
+        // plural の分岐の中も含めて select ごとに全ロケールのケースを集める
+        let mut select_cases: Vec<(String, Vec<String>)> = Vec::new();
+        for msg_value in message.messages.values() {
+            let mut parser = icu_messageformat_parser::Parser::new(&msg_value.value, &self.parser_options);
+            let parsed = match parser.parse() {
+                Ok(parsed) => parsed,
+                Err(_) => return Ok(vec![message.clone()]),
+            };
+            let mut locale_selects = Vec::new();
+            collect_hoistable_selects(&parsed, self.converter_options.device_argument.as_deref(), &mut locale_selects);
+            for (argument, cases) in locale_selects {
+                let index = match select_cases.iter().position(|(existing, _)| existing == argument) {
+                    Some(index) => index,
+                    None => {
+                        select_cases.push((argument.to_string(), Vec::new()));
+                        select_cases.len() - 1
+                    }
+                };
+                for case_key in cases {
+                    if !select_cases[index].1.iter().any(|existing| existing == case_key) {
+                        select_cases[index].1.push(case_key.to_string());
+                    }
+                }
+            }
+        }
+        let selects: Vec<(&str, Vec<&str>)> = select_cases
+            .iter()
+            .map(|(argument, cases)| (argument.as_str(), cases.iter().map(String::as_str).collect()))
+            .collect();
+        if selects.is_empty() {
+            return Ok(vec![message.clone()]);
+        }
+        
+        let combinations = self.case_combinations(&message.key, "selects", &selects)?;
+        
+        let mut split_messages = Vec::with_capacity(combinations.len());
+        for combination in &combinations {
+            let mut new_key = message.key.clone();
+            for (_, case_key) in combination {
+                new_key.push('_');
+                new_key.push_str(case_key);
+            }
+            let mut new_messages = LinkedHashMap::with_capacity(message.messages.len());
+            
+            for (locale, msg_value) in &message.messages {
+                let new_value = self.replace_selects_with_cases(&msg_value.value, combination);
+                new_messages.insert(locale.clone(), split_message_value(msg_value, new_value, combination));
+            }
+            
+            let split_message = models::LocalizableICUMessage {
+                key: new_key,
+                messages: new_messages,
+                comment: message.comment.clone(),
+                extraction_state: message.extraction_state.clone(),
+                should_translate: message.should_translate,
+                placeholders: message.placeholders.clone(),
+            };
+            // 選ばれたケースの中に select があれば、さらに分割する
+            if self.has_select_elements(&split_message) {
+                split_messages.extend(self.split_select_message(&split_message)?);
+            } else {
+                split_messages.push(split_message);
+            }
+        }
+        
+        if split_messages.len() > self.converter_options.max_select_combinations {
+            return Err(format!(
+                "Splitting key '{}' on nested selects would generate {} keys, more than {} keys allowed. Reduce the number of select cases or raise max_select_combinations.",
+                message.key,
+                split_messages.len(),
+                self.converter_options.max_select_combinations
+            ));
+        }
+        
+        Ok(split_messages)
+    }
+
+    /// Replaces each hoistable select with the branch chosen for its argument in
+    /// `cases`, falling back to `other` when a locale doesn't define that case.
+    /// The rest of the message, including nested plurals, is kept as ICU text.
+    fn replace_selects_with_cases(&self, original_value: &str, cases: &[(&str, &str)]) -> String {
+        let mut parser = icu_messageformat_parser::Parser::new(original_value, &self.parser_options);
+        match parser.parse() {
+            Ok(parsed) => icu_printer::print(&replace_selects(
+                parsed,
+                cases,
+                self.converter_options.device_argument.as_deref(),
+            )),
+            Err(_) => original_value.to_string(),
+        }
+    }
+
+}
+
+/// Lists argument kinds for an error message, e.g. `a plural and a number`.
+fn describe_kinds(kinds: &BTreeSet<ArgumentUsage>) -> String {
+    kinds.iter().map(ArgumentUsage::describe).collect::<Vec<_>>().join(" and ")
+}
+
+/// The specifiers of the key's placeholders that declare a known type.
+fn declared_specifiers(message: &models::LocalizableICUMessage) -> HashMap<String, String> {
+    message
+        .placeholders
+        .iter()
+        .flatten()
+        .filter_map(|(name, placeholder)| {
+            let placeholder_type: models::PlaceholderType = placeholder.placeholder_type.as_ref()?.parse().ok()?;
+            Some((name.clone(), placeholder_type.specifier().to_string()))
+        })
+        .collect()
+}
+
+/// One line per described placeholder for the key's comment, e.g.
+/// `count: Number of photos (e.g. 3)`.
+fn placeholder_descriptions(message: &models::LocalizableICUMessage) -> Option<String> {
+    let lines: Vec<String> = message
+        .placeholders
+        .iter()
+        .flatten()
+        .filter_map(|(name, placeholder)| match (&placeholder.description, &placeholder.example) {
+            (Some(description), Some(example)) => Some(format!("{}: {} (e.g. {})", name, description, example)),
+            (Some(description), None) => Some(format!("{}: {}", name, description)),
+            (None, Some(example)) => Some(format!("{}: e.g. {}", name, example)),
+            (None, None) => None,
+        })
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// The value of a key split off `msg_value` on the cases in `combination`. A
+/// variation state set for a chosen case becomes the key's state, since the
+/// case is no longer a variation.
+fn split_message_value(
+    msg_value: &models::LocalizableICUMessageValue,
+    value: String,
+    combination: &[(&str, &str)],
+) -> models::LocalizableICUMessageValue {
+    let case_state = combination
+        .iter()
+        .rev()
+        .find_map(|(argument, case)| msg_value.variation_state(argument, &[case]));
+    models::LocalizableICUMessageValue {
+        value,
+        state: case_state.or(msg_value.state.as_ref()).cloned(),
+        variation_states: msg_value.variation_states.clone(),
+    }
+}
+
+/// Collects the names of the tags used anywhere in `elements`, including plural
+/// and select branches.
+fn collect_tags(elements: &[AstElement], tags: &mut std::collections::BTreeSet<String>) {
+    for element in elements {
+        match element {
+            AstElement::Tag { value, children, .. } => {
+                tags.insert(value.to_string());
+                collect_tags(children, tags);
+            }
+            AstElement::Plural { options, .. } | AstElement::Select { options, .. } => {
+                for (_, option) in &options.0 {
+                    collect_tags(&option.value, tags);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Collects the selects that can be hoisted to the top of the message: those at
+/// the top level or inside plural branches and tags. Selects inside another
+/// select's case are left for the next round of splitting, except in the cases of
+/// the device select, which is kept for device variations. Case keys of the same
+/// argument are merged in order of appearance.
+fn collect_hoistable_selects<'a>(
+    elements: &'a [AstElement],
+    device_argument: Option<&str>,
+    selects: &mut Vec<(&'a str, Vec<&'a str>)>,
+) {
+    for element in elements {
+        match element {
+            AstElement::Select { value, options, .. } if device_argument == Some(value.as_str()) => {
+                for (_, option) in &options.0 {
+                    collect_hoistable_selects(&option.value, device_argument, selects);
+                }
+            }
+            AstElement::Select { value, options, .. } => {
+                let index = match selects.iter().position(|(argument, _)| *argument == value.as_str()) {
+                    Some(index) => index,
+                    None => {
+                        selects.push((value.as_str(), Vec::new()));
+                        selects.len() - 1
+                    }
+                };
+                for (case_key, _) in &options.0 {
+                    if !selects[index].1.contains(case_key) {
+                        selects[index].1.push(case_key);
+                    }
+                }
+            }
+            AstElement::Plural { options, .. } => {
+                for (_, option) in &options.0 {
+                    collect_hoistable_selects(&option.value, device_argument, selects);
+                }
+            }
+            AstElement::Tag { children, .. } => collect_hoistable_selects(children, device_argument, selects),
+            _ => {}
+        }
+    }
+}
+
+/// Replaces each select with the case chosen for its argument in `cases`
+/// (`other` when there is none). The device select is kept, with the selects in
+/// its cases replaced.
+fn replace_selects<'s>(
+    elements: Vec<AstElement<'s>>,
+    cases: &[(&str, &str)],
+    device_argument: Option<&str>,
+) -> Vec<AstElement<'s>> {
+    let mut replaced = Vec::with_capacity(elements.len());
+    for element in elements {
+        match element {
+            AstElement::Select { value, span, mut options } if device_argument == Some(value.as_str()) => {
+                for (_, option) in options.0.iter_mut() {
+                    option.value = replace_selects(std::mem::take(&mut option.value), cases, device_argument);
+                }
+                replaced.push(AstElement::Select { value, span, options });
+            }
+            AstElement::Select { value, options, .. } => {
+                let case_key = cases
+                    .iter()
+                    .find(|(argument, _)| *argument == value)
+                    .map(|(_, case_key)| *case_key)
+                    .unwrap_or("other");
+                let case_option = options.0.iter().find(|(key, _)| *key == case_key)
+                    .or_else(|| options.0.iter().find(|(key, _)| *key == "other"));
+                if let Some((_, option)) = case_option {
+                    replaced.extend(option.value.iter().cloned());
+                }
+            }
+            AstElement::Plural { value, plural_type, span, offset, mut options } => {
+                for (_, option) in options.0.iter_mut() {
+                    option.value = replace_selects(std::mem::take(&mut option.value), cases, device_argument);
+                }
+                replaced.push(AstElement::Plural { value, plural_type, span, offset, options });
+            }
+            AstElement::Tag { value, span, children } => {
+                let children = Box::new(replace_selects(*children, cases, device_argument));
+                replaced.push(AstElement::Tag { value, span, children });
+            }
+            element => replaced.push(element),
+        }
+    }
+    replaced
+
+}
+
+/// Collects every `selectordinal` argument with its cases, wherever it appears.
+fn collect_ordinals(elements: &[AstElement], ordinals: &mut Vec<(String, Vec<String>)>) {
+    for element in elements {
+        match element {
+            AstElement::Plural { value, plural_type, options, .. } => {
+                if validation::is_ordinal(plural_type) {
+                    let index = match ordinals.iter().position(|(argument, _)| argument == value) {
+                        Some(index) => index,
+                        None => {
+                            ordinals.push((value.clone(), Vec::new()));
+                            ordinals.len() - 1
+                        }
+                    };
+                    for (case_key, _) in &options.0 {
+                        if !ordinals[index].1.iter().any(|existing| existing == case_key) {
+                            ordinals[index].1.push(case_key.to_string());
+                        }
+                    }
+                }
+                for (_, option) in &options.0 {
+                    collect_ordinals(&option.value, ordinals);
+                }
+            }
+            AstElement::Select { options, .. } => {
+                for (_, option) in &options.0 {
+                    collect_ordinals(&option.value, ordinals);
+                }
+            }
+            AstElement::Tag { children, .. } => collect_ordinals(children, ordinals),
+            _ => {}
+        }
+    }
+}
+
+/// Replaces each `selectordinal` with its case from `cases` (or `other`), turning
+/// the case's `#` into a number argument.
+fn replace_ordinals<'s>(elements: Vec<AstElement<'s>>, cases: &[(&str, &str)]) -> Vec<AstElement<'s>> {
+    let mut replaced = Vec::with_capacity(elements.len());
+    for element in elements {
+        match element {
+            AstElement::Plural { value, plural_type, span, offset, mut options } => {
+                if validation::is_ordinal(&plural_type) {
+                    let case_key = cases
+                        .iter()
+                        .find(|(argument, _)| *argument == value)
+                        .map(|(_, case_key)| *case_key)
+                        .unwrap_or("other");
+                    let case_option = options.0.iter().find(|(key, _)| *key == case_key)
+                        .or_else(|| options.0.iter().find(|(key, _)| *key == "other"));
+                    if let Some((_, option)) = case_option {
+                        let chosen = pound_to_number(option.value.clone(), &value);
+                        replaced.extend(replace_ordinals(chosen, cases));
+                    }
+                } else {
+                    for (_, option) in options.0.iter_mut() {
+                        option.value = replace_ordinals(std::mem::take(&mut option.value), cases);
+                    }
+                    replaced.push(AstElement::Plural { value, plural_type, span, offset, options });
+                }
+            }
+            AstElement::Select { value, span, mut options } => {
+                for (_, option) in options.0.iter_mut() {
+                    option.value = replace_ordinals(std::mem::take(&mut option.value), cases);
+                }
+                replaced.push(AstElement::Select { value, span, options });
+            }
+            AstElement::Tag { value, span, children } => {
+                let children = Box::new(replace_ordinals(*children, cases));
+                replaced.push(AstElement::Tag { value, span, children });
+            }
+            element => replaced.push(element),
+        }
+    }
+    replaced
+}
+
+/// Replaces `#` with `{argument, number}`, leaving nested plurals' own `#` alone.
+fn pound_to_number<'s>(elements: Vec<AstElement<'s>>, argument: &str) -> Vec<AstElement<'s>> {
+    elements
+        .into_iter()
+        .map(|element| match element {
+            AstElement::Pound(_) => AstElement::Number {
+                value: argument.to_string(),
+                span: None,
+                style: None,
+            },
+            AstElement::Tag { value, span, children } => AstElement::Tag {
+                value,
+                span,
+                children: Box::new(pound_to_number(*children, argument)),
+            },
+            element => element,
+        })
+        .collect()
+}
+
+/// Collects the exact plural cases that have no variation of their own, per
+/// plural argument, wherever the plural appears.
+fn collect_exact_cases(elements: &[AstElement], exact_cases: &mut Vec<(String, Vec<String>)>) {
+    for element in elements {
+        match element {
+            AstElement::Plural { value, offset, options, .. } => {
+                for (selector, _) in &options.0 {
+                    if unrepresentable_exact_case(selector, *offset).is_none() {
+                        continue;
+                    }
+                    let index = match exact_cases.iter().position(|(argument, _)| argument == value) {
+                        Some(index) => index,
+                        None => {
+                            exact_cases.push((value.clone(), Vec::new()));
+                            exact_cases.len() - 1
+                        }
+                    };
+                    if !exact_cases[index].1.iter().any(|existing| existing == selector) {
+                        exact_cases[index].1.push(selector.to_string());
+                    }
+                }
+                for (_, option) in &options.0 {
+                    collect_exact_cases(&option.value, exact_cases);
+                }
+            }
+            AstElement::Select { options, .. } => {
+                for (_, option) in &options.0 {
+                    collect_exact_cases(&option.value, exact_cases);
+                }
+            }
+            AstElement::Tag { children, .. } => collect_exact_cases(children, exact_cases),
+            _ => {}
+        }
+    }
+}
+
+/// Replaces each plural with the exact case chosen for it in `cases`, turning
+/// `#` into a number argument. An empty choice, or a locale without the chosen
+/// case, keeps the plural without its unrepresentable exact cases.
+fn replace_exact_cases<'s>(elements: Vec<AstElement<'s>>, cases: &[(&str, &str)]) -> Vec<AstElement<'s>> {
+    let mut replaced = Vec::with_capacity(elements.len());
+    for element in elements {
+        match element {
+            AstElement::Plural { value, plural_type, span, offset, mut options } => {
+                let case_key = cases
+                    .iter()
+                    .find(|(argument, _)| *argument == value)
+                    .map(|(_, case_key)| *case_key)
+                    .unwrap_or("");
+                let chosen = options.0.iter().position(|(key, _)| !case_key.is_empty() && *key == case_key);
+                match chosen {
+                    Some(index) => {
+                        let option = options.0.swap_remove(index).1;
+                        replaced.extend(replace_exact_cases(pound_to_number(option.value, &value), cases));
+                    }
+                    None => {
+                        options.0.retain(|(key, _)| unrepresentable_exact_case(key, offset).is_none());
+                        for (_, option) in options.0.iter_mut() {
+                            option.value = replace_exact_cases(std::mem::take(&mut option.value), cases);
+                        }
+                        replaced.push(AstElement::Plural { value, plural_type, span, offset, options });
+                    }
+                }
+            }
+            AstElement::Select { value, span, mut options } => {
+                for (_, option) in options.0.iter_mut() {
+                    option.value = replace_exact_cases(std::mem::take(&mut option.value), cases);
+                }
+                replaced.push(AstElement::Select { value, span, options });
+            }
+            AstElement::Tag { value, span, children } => {
+                let children = Box::new(replace_exact_cases(*children, cases));
+                replaced.push(AstElement::Tag { value, span, children });
+            }
+            element => replaced.push(element),
+        }
+    }
+    replaced
+}
+
+/// Renames each unrepresentable exact case to the category `locale` selects for
+/// its value, or drops it when that category already has a case.
+fn fold_exact_cases<'s>(elements: Vec<AstElement<'s>>, locale: &str) -> Vec<AstElement<'s>> {
+    elements
+        .into_iter()
+        .map(|element| match element {
+            AstElement::Plural { value, plural_type, span, offset, options } => {
+                let keywords: Vec<&str> = options.0.iter().map(|(key, _)| *key).collect();
+                let mut folded = Vec::with_capacity(options.0.len());
+                let mut options = options;
+                for (key, mut option) in std::mem::take(&mut options.0) {
+                    option.value = fold_exact_cases(option.value, locale);
+                    match unrepresentable_exact_case(key, offset) {
+                        Some(exact) => {
+                            let category = validation::fold_category(locale, exact - offset).as_str();
+                            let taken = keywords.contains(&category)
+                                || folded.iter().any(|(existing, _)| *existing == category);
+                            if !taken {
+                                folded.push((category, option));
+                            }
+                        }
+                        None => folded.push((key, option)),
+                    }
+                }
+                options.0 = folded;
+                AstElement::Plural { value, plural_type, span, offset, options }
+            }
+            AstElement::Select { value, span, mut options } => {
+                for (_, option) in options.0.iter_mut() {
+                    option.value = fold_exact_cases(std::mem::take(&mut option.value), locale);
+                }
+                AstElement::Select { value, span, options }
+            }
+            AstElement::Tag { value, span, children } => AstElement::Tag {
+                value,
+                span,
+                children: Box::new(fold_exact_cases(*children, locale)),
+            },
+            element => element,
+        })
+        .collect()
+}
 
 #[cfg(test)]
 mod tests {
@@ -359,21 +1582,24 @@ mod tests {
             "ja".to_string(),
             LocalizableICUMessageValue {
                 value: "こんにちは {your_name}、私は {my_name} です。".to_string(),
-                state: "translated".to_string(),
+                state: Some("translated".to_string()),
+                ..Default::default()
             },
         );
         messages.insert(
             "en".to_string(),
             LocalizableICUMessageValue {
                 value: "Hello {your_name}, I'm {my_name}.".to_string(),
-                state: "needs_review".to_string(),
+                state: Some("needs_review".to_string()),
+                ..Default::default()
             },
         );
         messages.insert(
             "ko".to_string(),
             LocalizableICUMessageValue {
                 value: "안녕하세요 {your_name}, 저는 {my_name} 입니다.".to_string(),
-                state: "translated".to_string(),
+                state: Some("translated".to_string()),
+                ..Default::default()
             },
         );
 
@@ -381,6 +1607,7 @@ mod tests {
             key: "hello".to_string(),
             messages,
             comment: Some("A greeting message with both the user's name and speaker's name".to_string()),
+            ..Default::default()
         };
         let converter = super::XCStringConverter::new(
             "ja".to_string(),
@@ -391,31 +1618,144 @@ mod tests {
         let xcstring = xcstrings.strings.get("hello").unwrap();
         assert_eq!(xcstring.localizations.len(), 3);
         assert_eq!(
-            xcstring.localizations.get("ja").unwrap().string_unit.value,
+            xcstring.localizations.get("ja").unwrap().string_unit.as_ref().unwrap().value,
             "こんにちは %1$@、私は %2$@ です。"
         );
         assert_eq!(
-            xcstring.localizations.get("en").unwrap().string_unit.value,
+            xcstring.localizations.get("en").unwrap().string_unit.as_ref().unwrap().value,
             "Hello %1$@, I'm %2$@."
         );
         assert_eq!(
-            xcstring.localizations.get("ko").unwrap().string_unit.value,
+            xcstring.localizations.get("ko").unwrap().string_unit.as_ref().unwrap().value,
             "안녕하세요 %1$@, 저는 %2$@ 입니다."
         );
         assert_eq!(
-            xcstring.localizations.get("ja").unwrap().string_unit.localization_state,
+            xcstring.localizations.get("ja").unwrap().string_unit.as_ref().unwrap().localization_state,
             super::xcstrings::LocalizationState::Translated
         );
         assert_eq!(
-            xcstring.localizations.get("en").unwrap().string_unit.localization_state,
+            xcstring.localizations.get("en").unwrap().string_unit.as_ref().unwrap().localization_state,
             super::xcstrings::LocalizationState::NeedsReview
         );
         assert_eq!(
-            xcstring.localizations.get("ko").unwrap().string_unit.localization_state,
+            xcstring.localizations.get("ko").unwrap().string_unit.as_ref().unwrap().localization_state,
             super::xcstrings::LocalizationState::Translated
         );
     }
 
+    #[test]
+    fn test_key_states() {
+        let message = |key: &str, state: &str, extraction_state: Option<&str>, should_translate: Option<bool>| {
+            let mut messages = LinkedHashMap::new();
+            messages.insert(
+                "en".to_string(),
+                LocalizableICUMessageValue {
+                    value: "Hello".to_string(),
+                    state: Some(state.to_string()),
+                    ..Default::default()
+                },
+            );
+            super::models::LocalizableICUMessage {
+                key: key.to_string(),
+                messages,
+                comment: None,
+                extraction_state: extraction_state.map(str::to_string),
+                should_translate,
+                ..Default::default()
+            }
+        };
+        let converter = converter_with("en", ConverterOptions::default());
+
+        let xcstrings = converter
+            .convert(vec![
+                message("plain", "new", None, None),
+                message("migrated", "stale", Some("migrated"), Some(true)),
+                message("brand", "translated", Some("extracted_with_value"), Some(false)),
+            ])
+            .unwrap();
+        let json = serde_json::to_value(&xcstrings).unwrap();
+        assert_eq!(json["strings"]["plain"]["extractionState"], "manual");
+        assert_eq!(json["strings"]["plain"]["localizations"]["en"]["stringUnit"]["state"], "new");
+        assert!(json["strings"]["plain"].get("shouldTranslate").is_none());
+        assert_eq!(json["strings"]["migrated"]["extractionState"], "migrated");
+        assert_eq!(json["strings"]["migrated"]["localizations"]["en"]["stringUnit"]["state"], "stale");
+        // true は既定値なので出力しない
+        assert!(json["strings"]["migrated"].get("shouldTranslate").is_none());
+        assert_eq!(json["strings"]["brand"]["extractionState"], "extracted_with_value");
+        assert_eq!(json["strings"]["brand"]["shouldTranslate"], false);
+
+        // 未知の extraction state はキー単位のエラー
+        let unknown = message("unknown", "translated", Some("automatic"), None);
+        let diagnostics = converter.validate(std::slice::from_ref(&unknown));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].locale, None);
+        assert!(converter.convert(vec![unknown]).unwrap_err().contains("Invalid extraction state 'automatic'"));
+    }
+
+    #[test]
+    fn test_state_precedence() {
+        let convert = |state: Option<&str>, variation_states: &[(&str, &str, &str)]| {
+            let mut argument_states: LinkedHashMap<String, LinkedHashMap<String, String>> = LinkedHashMap::new();
+            for (argument, case, state) in variation_states {
+                argument_states
+                    .entry(argument.to_string())
+                    .or_default()
+                    .insert(case.to_string(), state.to_string());
+            }
+            let mut messages = LinkedHashMap::new();
+            messages.insert(
+                "en".to_string(),
+                LocalizableICUMessageValue {
+                    value: "{gender, select, female {She} other {They}} sent {count, plural, =0 {no files} one {a file} other {# files}}".to_string(),
+                    state: state.map(str::to_string),
+                    variation_states: Some(argument_states),
+                },
+            );
+            let message = super::models::LocalizableICUMessage {
+                key: "sent".to_string(),
+                messages,
+                comment: None,
+                ..Default::default()
+            };
+            let converter = converter_with("en", ConverterOptions {
+                localization_state: super::xcstrings::LocalizationState::NeedsReview,
+                ..ConverterOptions::default()
+            });
+            converter.convert(vec![message])
+        };
+        // キーごとに string unit、zero、other の state を返す
+        let states = |xcstrings: &super::xcstrings::XCStrings, key: &str| {
+            let json = serde_json::to_value(&xcstrings.strings.get(key).unwrap().localizations["en"]).unwrap();
+            let plural = &json["substitutions"]["count"]["variations"]["plural"];
+            vec![
+                json["stringUnit"]["state"].as_str().unwrap().to_string(),
+                plural["zero"]["stringUnit"]["state"].as_str().unwrap().to_string(),
+                plural["other"]["stringUnit"]["state"].as_str().unwrap().to_string(),
+            ]
+        };
+
+        // state がなければ CLI の既定値をすべてに使う
+        let xcstrings = convert(None, &[]).unwrap();
+        assert_eq!(states(&xcstrings, "sent_other"), vec!["needs_review"; 3]);
+
+        // メッセージの state は既定値より優先される
+        let xcstrings = convert(Some("translated"), &[]).unwrap();
+        assert_eq!(states(&xcstrings, "sent_other"), vec!["translated"; 3]);
+
+        // バリエーションの state はメッセージの state より優先される
+        // 分割された select のケースはそのキーの state になる
+        let xcstrings = convert(Some("translated"), &[("count", "=0", "new"), ("gender", "female", "stale")]).unwrap();
+        assert_eq!(states(&xcstrings, "sent_other"), vec!["translated", "new", "translated"]);
+        assert_eq!(states(&xcstrings, "sent_female"), vec!["stale", "new", "stale"]);
+
+        // String Catalog のバリエーション名でも指定できる
+        let xcstrings = convert(Some("translated"), &[("count", "zero", "new")]).unwrap();
+        assert_eq!(states(&xcstrings, "sent_other"), vec!["translated", "new", "translated"]);
+
+        let error = convert(None, &[("count", "one", "done")]).unwrap_err();
+        assert!(error.contains("case 'one' of 'count': Invalid localization state 'done'"), "{}", error);
+    }
+
     #[test]
     fn test_variable_count_mismatch() {
         let mut messages = LinkedHashMap::new();
@@ -423,14 +1763,16 @@ mod tests {
             "en".to_string(),
             LocalizableICUMessageValue {
                 value: "Hello {name} and {age}!".to_string(),
-                state: "translated".to_string(),
+                state: Some("translated".to_string()),
+                ..Default::default()
             },
         );
         messages.insert(
             "ja".to_string(),
             LocalizableICUMessageValue {
                 value: "こんにちは {name} さん！".to_string(),
-                state: "translated".to_string(),
+                state: Some("translated".to_string()),
+                ..Default::default()
             },
         );
 
@@ -438,6 +1780,7 @@ mod tests {
             key: "inconsistent_test".to_string(),
             messages,
             comment: Some("Test case with inconsistent variable count".to_string()),
+            ..Default::default()
         };
         let converter = super::XCStringConverter::new(
             "en".to_string(),
@@ -458,14 +1801,16 @@ mod tests {
             "en".to_string(),
             LocalizableICUMessageValue {
                 value: "Hello {firstName}!".to_string(),
-                state: "translated".to_string(),
+                state: Some("translated".to_string()),
+                ..Default::default()
             },
         );
         messages.insert(
             "ja".to_string(),
             LocalizableICUMessageValue {
                 value: "こんにちは {lastName} さん！".to_string(),
-                state: "translated".to_string(),
+                state: Some("translated".to_string()),
+                ..Default::default()
             },
         );
 
@@ -473,6 +1818,7 @@ mod tests {
             key: "wrong_variable_names".to_string(),
             messages,
             comment: Some("Test case with different variable names".to_string()),
+            ..Default::default()
         };
         let converter = super::XCStringConverter::new(
             "en".to_string(),
@@ -486,5 +1832,828 @@ mod tests {
         assert!(error_message.contains("lastName"));
         assert!(error_message.contains("firstName"));
     }
-}
 
+    #[test]
+    fn test_variable_kind_mismatch() {
+        let convert = |values: &[(&str, &str)]| try_convert_single("en", ConverterOptions::default(), "kinds", values);
+
+        let error = convert(&[("en", "{count, plural, one {# file} other {# files}}"), ("ja", "{count}個のファイル")]).unwrap_err();
+        assert_eq!(
+            error,
+            "Variable kind mismatch in key 'kinds'. Language 'en' uses 'count' as a plural, but 'ja' uses it as a plain argument"
+        );
+
+        let error = convert(&[("en", "Total: {price, number}"), ("de", "Summe: {price}")]).unwrap_err();
+        assert!(error.contains("Language 'en' uses 'price' as a number, but 'de' uses it as a plain argument"), "{}", error);
+
+        let error = convert(&[("en", "Due {when, date}"), ("de", "Fällig {when, time} {when, date}")]).unwrap_err();
+        assert!(error.contains("'de' uses it as a date and a time"), "{}", error);
+
+        // plural の分岐の中で自分の引数を使うのは plural の数と同じ
+        assert!(convert(&[
+            ("en", "{count, plural, one {# file} other {{count, number} files}}"),
+            ("ja", "{count, plural, other {{count}個のファイル}}"),
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn test_format_specifier_mismatch() {
+        let convert = |values: &[(&str, &str)]| try_convert_single("en", ConverterOptions::default(), "ratio", values);
+
+        let error = convert(&[("en", "{ratio, number, percent} done"), ("ja", "{ratio, number} 完了")]).unwrap_err();
+        assert_eq!(
+            error,
+            "Format specifier mismatch in key 'ratio'. Language 'en' formats 'ratio' as %@, but 'ja' formats it as %lld; use the same number style or declare the placeholder"
+        );
+
+        let error = convert(&[("en", "{w, number, ::.00} kg"), ("ja", "{w, number} kg")]).unwrap_err();
+        assert!(error.contains("Language 'en' formats 'w' as %.2f, but 'ja' formats it as %lld"), "{}", error);
+
+        // 同じスタイルなら書き方が違ってもよい
+        assert!(convert(&[("en", "{w, number, ::.00} kg"), ("ja", "{w, number, ::.00}キロ")]).is_ok());
+    }
+
+    fn single_message(key: &str, values: &[(&str, &str)]) -> super::models::LocalizableICUMessage {
+        let mut messages = LinkedHashMap::new();
+        for (locale, value) in values {
+            messages.insert(
+                locale.to_string(),
+                LocalizableICUMessageValue {
+                    value: value.to_string(),
+                    state: Some("translated".to_string()),
+                    ..Default::default()
+                },
+            );
+        }
+        super::models::LocalizableICUMessage {
+            key: key.to_string(),
+            messages,
+            comment: None,
+            ..Default::default()
+        }
+    }
+
+    fn converter_with(source_language: &str, options: ConverterOptions) -> super::XCStringConverter {
+        super::XCStringConverter::new(
+            source_language.to_string(),
+            options,
+            icu_messageformat_parser::ParserOptions::default(),
+        )
+    }
+
+    fn try_convert_single(
+        source_language: &str,
+        options: ConverterOptions,
+        key: &str,
+        values: &[(&str, &str)],
+    ) -> Result<super::xcstrings::XCStrings, String> {
+        converter_with(source_language, options).convert(vec![single_message(key, values)])
+    }
+
+    fn convert_single(source_language: &str, key: &str, values: &[(&str, &str)]) -> super::xcstrings::XCString {
+        let mut xcstrings = try_convert_single(source_language, ConverterOptions::default(), key, values).unwrap();
+        xcstrings.strings.remove(key).unwrap()
+    }
+
+    #[test]
+    fn test_reordered_arguments_follow_source_language() {
+        let xcstring = convert_single(
+            "en",
+            "key",
+            &[
+                ("es", "¡Hola, {name2} y {name1}!"),
+                ("en", "Hello, {name1} and {name2}!"),
+            ],
+        );
+        assert_eq!(
+            xcstring.localizations.get("en").unwrap().string_unit.as_ref().unwrap().value,
+            "Hello, %1$@ and %2$@!"
+        );
+        assert_eq!(
+            xcstring.localizations.get("es").unwrap().string_unit.as_ref().unwrap().value,
+            "¡Hola, %2$@ y %1$@!"
+        );
+    }
+
+    #[test]
+    fn test_reordered_arguments_with_plural() {
+        let xcstring = convert_single(
+            "en",
+            "files",
+            &[
+                ("en", "{owner} has {count, plural, one {one file} other {# files}}"),
+                ("ja", "{count, plural, other {#個のファイル}}を{owner}が持っています"),
+            ],
+        );
+        assert_eq!(
+            xcstring.localizations.get("en").unwrap().string_unit.as_ref().unwrap().value,
+            "%1$@ has %#@count@"
+        );
+        assert_eq!(
+            xcstring.localizations.get("ja").unwrap().string_unit.as_ref().unwrap().value,
+            "%#@count@を%1$@が持っています"
+        );
+    }
+
+    #[test]
+    fn test_reordered_arguments_with_date() {
+        let xcstring = convert_single(
+            "ja",
+            "meeting",
+            &[
+                ("en", "{name} meets you on {day, date, long} for {count, number} hours"),
+                ("ja", "{day, date, long}に{name}さんと{count, number}時間会います"),
+            ],
+        );
+        assert_eq!(
+            xcstring.localizations.get("ja").unwrap().string_unit.as_ref().unwrap().value,
+            "%1$@に%2$@さんと%3$lld時間会います"
+        );
+        assert_eq!(
+            xcstring.localizations.get("en").unwrap().string_unit.as_ref().unwrap().value,
+            "%2$@ meets you on %1$@ for %3$lld hours"
+        );
+    }
+
+    #[test]
+    fn test_comment_with_placeholder_legend() {
+        let mut messages = LinkedHashMap::new();
+        messages.insert(
+            "en".to_string(),
+            LocalizableICUMessageValue {
+                value: "{name} has {count, number} items".to_string(),
+                state: Some("translated".to_string()),
+                ..Default::default()
+            },
+        );
+        let message = super::models::LocalizableICUMessage {
+            key: "items".to_string(),
+            messages,
+            comment: Some("Inventory summary".to_string()),
+            ..Default::default()
+        };
+        let uncommented = super::models::LocalizableICUMessage {
+            key: "items_uncommented".to_string(),
+            comment: None,
+            ..message.clone()
+        };
+        let converter = converter_with("en", ConverterOptions::default());
+        let xcstrings = converter.convert(vec![message.clone(), uncommented.clone()]).unwrap();
+
+        let xcstring = xcstrings.strings.get("items").unwrap();
+        assert_eq!(
+            xcstring.comment.as_deref(),
+            Some("Inventory summary\n%1$@ = name, %2$lld = count")
+        );
+        assert_eq!(xcstring.is_comment_auto_generated, None);
+
+        let xcstring = xcstrings.strings.get("items_uncommented").unwrap();
+        assert_eq!(xcstring.comment.as_deref(), Some("%1$@ = name, %2$lld = count"));
+        assert_eq!(xcstring.is_comment_auto_generated, Some(true));
+
+        let converter = converter_with("en", ConverterOptions {
+            placeholder_legend: false,
+            ..ConverterOptions::default()
+        });
+        let xcstrings = converter.convert(vec![message, uncommented]).unwrap();
+        assert_eq!(
+            xcstrings.strings.get("items").unwrap().comment.as_deref(),
+            Some("Inventory summary")
+        );
+        assert_eq!(xcstrings.strings.get("items_uncommented").unwrap().comment, None);
+    }
+
+    fn validate_single(values: &[(&str, &str)]) -> Vec<super::models::Diagnostic> {
+        converter_with("en", ConverterOptions::default()).validate(&[single_message("apples", values)])
+    }
+
+    #[test]
+    fn test_validate_plural_categories() {
+        use super::models::{DiagnosticKind, Severity};
+
+        let diagnostics = validate_single(&[
+            ("en", "{count, plural, one {# apple} other {# apples}}"),
+            ("ja", "{count, plural, one {りんご1個} other {りんご#個}}"),
+            ("ru", "{count, plural, one {# яблоко} other {# яблок}}"),
+        ]);
+        let kinds: Vec<(&str, &DiagnosticKind)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.locale.as_deref().unwrap(), &diagnostic.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("ja", &DiagnosticKind::UnreachablePluralCategory {
+                    argument: "count".to_string(),
+                    category: "one".to_string(),
+                }),
+                ("ru", &DiagnosticKind::MissingPluralCategory {
+                    argument: "count".to_string(),
+                    category: "few".to_string(),
+                }),
+                ("ru", &DiagnosticKind::MissingPluralCategory {
+                    argument: "count".to_string(),
+                    category: "many".to_string(),
+                }),
+            ]
+        );
+        assert!(diagnostics.iter().all(|diagnostic| diagnostic.key == "apples"));
+        assert!(diagnostics.iter().all(|diagnostic| diagnostic.severity == Severity::Warning));
+    }
+
+    #[test]
+    fn test_validate_zero_semantics() {
+        use super::models::DiagnosticKind;
+
+        let diagnostics = validate_single(&[
+            ("en", "{count, plural, zero {no apples} one {# apple} other {# apples}}"),
+            ("lv", "{count, plural, =0 {nav ābolu} one {# ābols} other {# āboli}}"),
+        ]);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(matches!(
+            &diagnostics[0].kind,
+            DiagnosticKind::ZeroCategorySemantics { selector, .. } if selector == "zero"
+        ));
+        assert_eq!(diagnostics[0].locale.as_deref(), Some("en"));
+        assert!(matches!(
+            &diagnostics[1].kind,
+            DiagnosticKind::ZeroCategorySemantics { selector, detail, .. } if selector == "=0" && detail.contains("10")
+        ));
+        assert_eq!(diagnostics[1].locale.as_deref(), Some("lv"));
+    }
+
+    #[test]
+    fn test_plural_branch_arguments() {
+        let xcstring = convert_single(
+            "en",
+            "shared_files",
+            &[
+                ("en", "{count, plural, one {# file shared by {owner}} other {{count} files shared by {owner}}} on {day, date}"),
+                ("ja", "{day, date}に{owner}が{count, plural, other {#件のファイル}}を共有しました"),
+            ],
+        );
+        let en = xcstring.localizations.get("en").unwrap();
+        assert_eq!(en.string_unit.as_ref().unwrap().value, "%#@count@ on %3$@");
+        let en_substitution = en.substitutions.as_ref().unwrap().get("count").unwrap();
+        match &en_substitution.variations {
+            super::xcstrings::VariationType::Plural(variations) => {
+                assert_eq!(variations.get("one").unwrap().string_unit.value, "%arg file shared by %2$@");
+                assert_eq!(variations.get("other").unwrap().string_unit.value, "%arg files shared by %2$@");
+            }
+            _ => panic!("expected plural variations"),
+        }
+        assert_eq!(en_substitution.arg_num, 1);
+
+        let ja = xcstring.localizations.get("ja").unwrap();
+        assert_eq!(ja.string_unit.as_ref().unwrap().value, "%3$@に%2$@が%#@count@を共有しました");
+        let ja_substitution = ja.substitutions.as_ref().unwrap().get("count").unwrap();
+        match &ja_substitution.variations {
+            super::xcstrings::VariationType::Plural(variations) => {
+                assert_eq!(variations.get("other").unwrap().string_unit.value, "%arg件のファイル");
+            }
+            _ => panic!("expected plural variations"),
+        }
+    }
+
+    #[test]
+    fn test_literal_percent_is_escaped() {
+        let xcstring = convert_single(
+            "en",
+            "sale",
+            &[("en", "50% off {item}, {count, plural, one {# left at 100%} other {# left}}")],
+        );
+        let en = xcstring.localizations.get("en").unwrap();
+        assert_eq!(en.string_unit.as_ref().unwrap().value, "50%% off %1$@, %#@count@");
+        match &en.substitutions.as_ref().unwrap().get("count").unwrap().variations {
+            super::xcstrings::VariationType::Plural(variations) => {
+                assert_eq!(variations.get("one").unwrap().string_unit.value, "%arg left at 100%%");
+            }
+            _ => panic!("expected plural variations"),
+        }
+
+        let xcstring = convert_single("en", "plain", &[("en", "50% off")]);
+        assert_eq!(xcstring.localizations.get("en").unwrap().string_unit.as_ref().unwrap().value, "50% off");
+    }
+
+    #[test]
+    fn test_validate_printf_sequences() {
+        use super::models::DiagnosticKind;
+
+        let diagnostics = validate_single(&[("en", "Hello %@, {count, plural, other {%d apples}}")]);
+        let sequences: Vec<&DiagnosticKind> = diagnostics
+            .iter()
+            .map(|diagnostic| &diagnostic.kind)
+            .filter(|kind| matches!(kind, DiagnosticKind::PrintfStyleSequence { .. }))
+            .collect();
+        assert_eq!(
+            sequences,
+            vec![
+                &DiagnosticKind::PrintfStyleSequence { sequence: "%@".to_string() },
+                &DiagnosticKind::PrintfStyleSequence { sequence: "%d".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_combination_limit() {
+        let message = single_message("combined", &[("en", "{a, select, x {X} y {Y} other {Z}} {b, select, x {X} y {Y} other {Z}}")]);
+
+        let converter = converter_with("en", ConverterOptions {
+            max_select_combinations: 9,
+            ..ConverterOptions::default()
+        });
+        let xcstrings = converter.convert(vec![message.clone()]).unwrap();
+        assert_eq!(xcstrings.strings.len(), 9);
+        assert_eq!(
+            xcstrings.strings.get("combined_y_other").unwrap()
+                .localizations.get("en").unwrap().string_unit.as_ref().unwrap().value,
+            "Y Z"
+        );
+
+        let converter = converter_with("en", ConverterOptions {
+            max_select_combinations: 8,
+            ..ConverterOptions::default()
+        });
+        let error = converter.convert(vec![message]).unwrap_err();
+        assert!(error.contains("'combined'"));
+        assert!(error.contains("more than 8 keys"));
+    }
+
+    #[test]
+    fn test_ordinal_as_cardinal() {
+        use super::models::{DiagnosticKind, OrdinalHandling};
+
+        let message = single_message("place", &[
+            ("en", "{pos, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}"),
+            ("ja", "{pos, selectordinal, other {#位}}"),
+        ]);
+        let converter = converter_with("en", ConverterOptions {
+            ordinal_handling: OrdinalHandling::Cardinal,
+            ..ConverterOptions::default()
+        });
+
+        let xcstrings = converter.convert(vec![message.clone()]).unwrap();
+        let en = xcstrings.strings.get("place").unwrap().localizations.get("en").unwrap();
+        assert!(en.string_unit.is_none());
+        match &en.variations {
+            Some(super::xcstrings::LocalizationVariations::Plural(variations)) => {
+                assert_eq!(variations.get("two").unwrap().string_unit.value, "%lldnd");
+            }
+            _ => panic!("expected plural variations"),
+        }
+
+        // ja は序数も基数も other だけなので警告しない
+        let diagnostics = converter.validate(&[message]);
+        assert_eq!(
+            diagnostics,
+            vec![super::models::Diagnostic::warning(
+                "place",
+                "en",
+                DiagnosticKind::OrdinalAsCardinal {
+                    argument: "pos".to_string(),
+                    detail: "2 selects the 'other' case instead of 'two'".to_string(),
+                },
+            )]
+        );
+    }
+
+    #[test]
+    fn test_validate_ordinal_categories() {
+        use super::models::DiagnosticKind;
+
+        let diagnostics = validate_single(&[("en", "{pos, selectordinal, one {#st} other {#th}}")]);
+        let categories: Vec<&DiagnosticKind> = diagnostics.iter().map(|diagnostic| &diagnostic.kind).collect();
+        assert_eq!(
+            categories,
+            vec![
+                &DiagnosticKind::MissingPluralCategory { argument: "pos".to_string(), category: "two".to_string() },
+                &DiagnosticKind::MissingPluralCategory { argument: "pos".to_string(), category: "few".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plural_offset_rewrite_errors() {
+        use super::models::{DiagnosticKind, PluralOffsetHandling, Severity};
+
+        let message = single_message("attendees", &[("en", "{n, plural, offset:1 =0 {nobody} one {you and {n} other} other {you and # others}}")]);
+        let converter = converter_with("en", ConverterOptions {
+            plural_offset_handling: PluralOffsetHandling::Rewrite,
+            ..ConverterOptions::default()
+        });
+
+        let diagnostics = converter.validate(std::slice::from_ref(&message));
+        let offsets: Vec<&super::models::Diagnostic> = diagnostics
+            .iter()
+            .filter(|diagnostic| matches!(diagnostic.kind, DiagnosticKind::PluralOffset { .. }))
+            .collect();
+        assert_eq!(offsets.len(), 2);
+        assert!(offsets.iter().all(|diagnostic| diagnostic.severity == Severity::Error));
+        assert!(offsets[0].to_string().contains("case '=0' would have to match -1"));
+        assert!(offsets[1].to_string().contains("'{n}' would show the value after the offset"));
+
+        assert_eq!(converter.convert(vec![message]).unwrap_err(), offsets[0].to_string());
+
+        // 依頼の例: =0 はオフセットの前なので表せないが、# を使う分岐は変換できる
+        let options = ConverterOptions {
+            plural_offset_handling: PluralOffsetHandling::Rewrite,
+            ..ConverterOptions::default()
+        };
+        let error = try_convert_single("en", options.clone(), "attendees", &[
+            ("en", "{n, plural, offset:1 =0 {nobody} one {you and # other} other {you and # others}}"),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            error,
+            "error: key 'attendees' (en): plural 'n' with offset:1: case '=0' would have to match -1 after the offset, which String Catalogs can't express"
+        );
+        let xcstrings = try_convert_single("en", options, "attendees", &[
+            ("en", "{n, plural, offset:1 =1 {just you} one {you and # other} other {you and # others}}"),
+        ])
+        .unwrap();
+        let xcstring = xcstrings.strings.get("attendees").unwrap();
+        let json = serde_json::to_value(&xcstring.localizations).unwrap();
+        assert_eq!(json["en"]["variations"]["plural"]["zero"]["stringUnit"]["value"], "just you");
+        assert_eq!(json["en"]["variations"]["plural"]["one"]["stringUnit"]["value"], "you and %lld other");
+        assert_eq!(xcstring.comment.as_deref(), Some("%lld = n - 1"));
+    }
+
+    #[test]
+    fn test_fold_exact_cases() {
+        use super::models::{DiagnosticKind, ExactMatchHandling};
+
+        let message = single_message("items", &[
+            ("en", "{count, plural, =2 {a pair} one {# item} other {# items}}"),
+            ("ru", "{count, plural, =2 {пара} one {# предмет} many {# предметов} other {# предмета}}"),
+        ]);
+        let converter = converter_with("en", ConverterOptions {
+            exact_match_handling: ExactMatchHandling::Fold,
+            ..ConverterOptions::default()
+        });
+
+        // en の 2 は other に入るが other は既にあるので =2 は捨てられる。ru の 2 は few になる
+        let xcstrings = converter.convert(vec![message.clone()]).unwrap();
+        let xcstring = xcstrings.strings.get("items").unwrap();
+        let plural = |locale: &str| {
+            let localization = xcstring.localizations.get(locale).unwrap();
+            serde_json::to_value(localization).unwrap()["variations"]["plural"].clone()
+        };
+        assert_eq!(plural("en").as_object().unwrap().keys().collect::<Vec<_>>(), vec!["one", "other"]);
+        assert_eq!(plural("ru")["few"]["stringUnit"]["value"], "пара");
+
+        let diagnostics = converter.validate(std::slice::from_ref(&message));
+        let details: Vec<(&str, &str)> = diagnostics
+            .iter()
+            .filter_map(|diagnostic| match &diagnostic.kind {
+                DiagnosticKind::ExactMatchCase { detail, .. } => Some((diagnostic.locale.as_deref().unwrap(), detail.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            details,
+            vec![
+                ("en", "dropped, since the 'other' case it folds into already exists"),
+                ("ru", "folded into 'few', so every number in that category shows its text"),
+            ]
+        );
+
+        let converter = converter_with("en", ConverterOptions::default());
+        assert!(converter.convert(vec![message]).unwrap_err().contains("found '=2'"));
+    }
+
+    #[test]
+    fn test_split_exact_case_legend() {
+        let options = ConverterOptions {
+            exact_match_handling: super::models::ExactMatchHandling::SplitKeys,
+            ..ConverterOptions::default()
+        };
+        let xcstrings = try_convert_single("en", options, "socks", &[
+            ("en", "{count, plural, =2 {A pair of socks} one {# sock} other {# socks}}"),
+            ("ja", "{count, plural, other {靴下#足}}"),
+        ])
+        .unwrap();
+
+        // en の =2 には引数がないが、ja は plural のままなので凡例に count を載せる
+        let xcstring = xcstrings.strings.get("socks_eq2").unwrap();
+        assert_eq!(xcstring.comment.as_deref(), Some("%lld = count"));
+        let json = serde_json::to_value(&xcstring.localizations).unwrap();
+        assert_eq!(json["en"]["stringUnit"]["value"], "A pair of socks");
+        assert_eq!(json["ja"]["variations"]["plural"]["other"]["stringUnit"]["value"], "靴下%lld足");
+    }
+
+    #[test]
+    fn test_split_exact_case_escapes_percent() {
+        let options = ConverterOptions {
+            exact_match_handling: super::models::ExactMatchHandling::SplitKeys,
+            ..ConverterOptions::default()
+        };
+        let xcstrings = try_convert_single("en", options, "progress", &[
+            ("en", "{count, plural, =100 {100% done} other {# steps}}"),
+            ("ja", "{count, plural, other {#ステップ}}"),
+        ])
+        .unwrap();
+
+        // en の =100 には引数がないが、キーとしては count を受け取るので % をエスケープする
+        let json = serde_json::to_value(&xcstrings.strings.get("progress_eq100").unwrap().localizations).unwrap();
+        assert_eq!(json["en"]["stringUnit"]["value"], "100%% done");
+    }
+
+    #[test]
+    fn test_duplicate_exact_and_category_cases() {
+        let message = single_message("items", &[("en", "{count, plural, =0 {No items} zero {Nothing} other {# items}}")]);
+        let converter = converter_with("en", ConverterOptions::default());
+
+        let error = converter.convert(vec![message.clone()]).unwrap_err();
+        assert!(error.contains("Plural 'count' has both '=0' and 'zero' cases"));
+
+        let diagnostics = converter.validate(&[message]);
+        assert!(diagnostics.iter().any(|diagnostic| diagnostic.severity == super::models::Severity::Error
+            && diagnostic.to_string().contains("both '=0' and 'zero' would become the 'zero' variation")));
+    }
+
+    #[test]
+    fn test_time_arguments() {
+        let xcstring = convert_single("en", "meeting", &[
+            ("en", "Meeting at {start, time, short} on {day, date, medium}"),
+            ("ja", "{day, date, medium}の{start, time, short}に会議"),
+        ]);
+        assert_eq!(xcstring.localizations.get("en").unwrap().string_unit.as_ref().unwrap().value, "Meeting at %1$@ on %2$@");
+        assert_eq!(xcstring.localizations.get("ja").unwrap().string_unit.as_ref().unwrap().value, "%2$@の%1$@に会議");
+        assert_eq!(xcstring.comment.as_deref(), Some("%1$@ = start, %2$@ = day"));
+
+        let convert = |values: &[(&str, &str)]| try_convert_single("en", ConverterOptions::default(), "meeting", values);
+
+        // 時刻の引数も変数の整合性チェックの対象になる
+        let error = convert(&[("en", "Meeting at {start, time, short}"), ("ja", "会議があります")]).unwrap_err();
+        assert!(error.contains("Variable count mismatch in key 'meeting'"), "{}", error);
+
+        // 表現できない要素は空文字列ではなくエラーになる
+        let error = convert(&[("en", "Meeting at <b>{start, time, short}</b>")]).unwrap_err();
+        assert!(error.contains("Tag '<b>' can't be represented in a String Catalog"), "{}", error);
+    }
+
+    #[test]
+    fn test_markdown_tag_errors() {
+        let messages = |values: &[(&str, &str)]| single_message("notice", values);
+        let converter = converter_with("en", ConverterOptions {
+            markdown_tags: true,
+            ..ConverterOptions::default()
+        });
+
+        // Markdown にできないタグは validate と convert の両方で報告される
+        let unknown = messages(&[("en", "Read <u>this</u> and <b>that</b>")]);
+        let diagnostics = converter.validate(std::slice::from_ref(&unknown));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: key 'notice' (en): tag '<u>': only <b>, <i> and <link> have a Markdown equivalent"
+        );
+        let error = converter.convert(vec![unknown]).unwrap_err();
+        assert!(error.contains("Tag '<u>' has no Markdown equivalent"), "{}", error);
+
+        // ロケール間でタグの集合が異なるとエラーになる
+        let mismatched = messages(&[("en", "Read <b>this</b>"), ("ja", "<i>これ</i>を読んでください")]);
+        assert_eq!(
+            converter.convert(vec![mismatched]).unwrap_err(),
+            "Tag mismatch in key 'notice'. Language 'ja' uses tags {\"i\"}, but 'en' uses {\"b\"}"
+        );
+    }
+
+    #[test]
+    fn test_device_variation_errors() {
+        let convert = |value: &str| {
+            let options = ConverterOptions {
+                device_argument: Some("platform".to_string()),
+                ..ConverterOptions::default()
+            };
+            try_convert_single("en", options, "action", &[("en", value)])
+        };
+
+        // デバイスの select の中の select は分割される
+        let xcstrings = convert("{platform, select, iphone {{gender, select, female {Her} other {Their}} phone} other {{gender, select, female {Her} other {Their}} device}}").unwrap();
+        assert_eq!(
+            xcstrings.strings.keys().collect::<Vec<_>>(),
+            vec!["action_female", "action_other"]
+        );
+        let json = serde_json::to_value(&xcstrings.strings.get("action_female").unwrap().localizations).unwrap();
+        assert_eq!(json["en"]["variations"]["device"]["iphone"]["stringUnit"]["value"], "Her phone");
+        assert_eq!(json["en"]["variations"]["device"]["other"]["stringUnit"]["value"], "Her device");
+
+        let xcstrings = convert("{platform, select, ipod {Tap} other {Click}}").unwrap();
+        let json = serde_json::to_value(&xcstrings.strings.get("action").unwrap().localizations).unwrap();
+        assert_eq!(json["en"]["variations"]["device"]["ipod"]["stringUnit"]["value"], "Tap");
+
+        let error = convert("{platform, select, android {Tap} other {Click}}").unwrap_err();
+        assert!(error.contains("has the case 'android', which isn't a String Catalog device"), "{}", error);
+
+        let error = convert("{platform, select, iphone {{n, plural, other {# taps}}} other {{n, plural, other {# clicks}}}}").unwrap_err();
+        assert!(error.contains("'n' differs between the device cases of 'platform'"), "{}", error);
+    }
+
+    #[test]
+    fn test_whole_message_plurals() {
+        let xcstring = convert_single("en", "files", &[
+            ("en", "{count, plural, one {# file} other {# files}}"),
+            ("ja", "{count, plural, other {#個のファイル}}"),
+        ]);
+        let json = serde_json::to_value(&xcstring.localizations).unwrap();
+        assert_eq!(json["en"]["variations"]["plural"]["one"]["stringUnit"]["value"], "%lld file");
+        assert_eq!(json["ja"]["variations"]["plural"]["other"]["stringUnit"]["value"], "%lld個のファイル");
+        assert!(json["en"].get("stringUnit").is_none());
+        assert!(json["en"].get("substitutions").is_none());
+
+        // 他の引数があるときは位置指定子を使う
+        let xcstring = convert_single("en", "shared_files", &[
+            ("en", "{count, plural, one {# file from {owner}} other {# files from {owner}}}"),
+        ]);
+        let json = serde_json::to_value(&xcstring.localizations).unwrap();
+        assert_eq!(json["en"]["variations"]["plural"]["other"]["stringUnit"]["value"], "%1$lld files from %2$@");
+
+        let options = ConverterOptions {
+            whole_message_plurals: false,
+            ..ConverterOptions::default()
+        };
+        let xcstrings = try_convert_single("en", options, "files", &[("en", "{count, plural, one {# file} other {# files}}")]).unwrap();
+        let en = xcstrings.strings.get("files").unwrap().localizations.get("en").unwrap();
+        assert_eq!(en.string_unit.as_ref().unwrap().value, "%#@count@");
+        assert!(en.variations.is_none());
+    }
+
+    #[test]
+    fn test_nested_plural_errors() {
+        let convert = |value: &str| try_convert_single("en", ConverterOptions::default(), "files", &[("en", value)]);
+
+        let error = convert("{files, plural, one {# file in {folders, plural, one {# folder} other {# folders}}} other {# files in {folders, plural, other {some folders}}}}").unwrap_err();
+        assert!(error.contains("Plural 'folders' reads differently in the branches of plural 'files'"), "{}", error);
+
+        let error = convert("{files, plural, other {# files {files, plural, one {in one} other {in #}}}}").unwrap_err();
+        assert!(error.contains("Plural 'files' is nested inside a plural on the same argument"), "{}", error);
+
+        let error = convert("{folders, plural, other {# folders}}: {files, plural, other {# files in {folders, plural, other {some folders}}}}").unwrap_err();
+        assert!(error.contains("reads differently from the 'folders' plural outside it"), "{}", error);
+
+        // 外側と同じ内容なら同じ substitution を使う
+        let xcstrings = convert("{folders, plural, other {# folders}}: {files, plural, other {# files in {folders, plural, other {# folders}}}}").unwrap();
+        let en = xcstrings.strings.get("files").unwrap().localizations.get("en").unwrap();
+        assert_eq!(en.substitutions.as_ref().unwrap().keys().collect::<Vec<_>>(), vec!["folders", "files"]);
+    }
+
+    #[test]
+    fn test_placeholder_declarations() {
+        let message = |value: &str, placeholders: &[(&str, &str)]| {
+            let mut messages = LinkedHashMap::new();
+            messages.insert(
+                "en".to_string(),
+                LocalizableICUMessageValue {
+                    value: value.to_string(),
+                    ..Default::default()
+                },
+            );
+            super::models::LocalizableICUMessage {
+                key: "summary".to_string(),
+                messages,
+                placeholders: Some(
+                    placeholders
+                        .iter()
+                        .map(|(name, placeholder_type)| {
+                            (
+                                name.to_string(),
+                                super::models::Placeholder {
+                                    placeholder_type: Some(placeholder_type.to_string()),
+                                    ..Default::default()
+                                },
+                            )
+                        })
+                        .collect(),
+                ),
+                ..Default::default()
+            }
+        };
+        let converter = converter_with("en", ConverterOptions::default());
+        let diagnostics = |message: super::models::LocalizableICUMessage| {
+            converter
+                .validate(&[message])
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(diagnostics(message("{name} has {count, number}", &[("name", "String"), ("count", "Int")])).is_empty());
+
+        // 型が使い方と合わなければエラー
+        assert_eq!(
+            diagnostics(message("{count, plural, one {# item} other {# items}}", &[("count", "String")])),
+            vec!["error: key 'summary' (en): placeholder 'count' is declared as String but used as a plural"]
+        );
+        assert_eq!(
+            diagnostics(message("{when, date}", &[("when", "double")])),
+            vec!["error: key 'summary' (en): placeholder 'when' is declared as double but used as a date"]
+        );
+        assert_eq!(
+            diagnostics(message("{name}", &[("name", "Text")])),
+            vec!["error: key 'summary': placeholder 'name' has the unknown type 'Text'; use String, int, Int32, double or DateTime"]
+        );
+
+        // 使われていない宣言と宣言のない引数は警告
+        assert_eq!(
+            diagnostics(message("{name}", &[("count", "int")])),
+            vec![
+                "warning: key 'summary': placeholder 'count' is declared but no message uses it",
+                "warning: key 'summary': argument 'name' isn't declared in placeholders",
+            ]
+        );
+
+        // 分割される select も型のチェックの対象
+        let error = converter
+            .convert(vec![message("{gender, select, male {He} other {They}}", &[("gender", "int")])])
+            .unwrap_err();
+        assert!(error.contains("placeholder 'gender' is declared as int but used as a select"), "{}", error);
+        assert!(converter.convert(vec![message("{name}", &[("count", "int")])]).is_ok());
+    }
+
+    #[test]
+    fn test_select_case_union() {
+        let message = single_message("left", &[
+            ("en", "{gender, select, male {He} other {They}} left"),
+            ("ja", "{gender, select, male {彼} female {彼女} other {その人}}が退出"),
+            ("fr", "{gender, select, female {Elle} other {Iel}} est parti"),
+        ]);
+
+        // 全ロケールのケースの和集合で分割し、ないケースは other を使う
+        let converter = converter_with("en", ConverterOptions::default());
+        let xcstrings = converter.convert(vec![message.clone()]).unwrap();
+        let keys: Vec<&String> = xcstrings.strings.keys().collect();
+        assert_eq!(keys, vec!["left_male", "left_other", "left_female"]);
+        let value = |key: &str, locale: &str| {
+            xcstrings.strings.get(key).unwrap().localizations.get(locale).unwrap()
+                .string_unit.as_ref().unwrap().value.clone()
+        };
+        assert_eq!(value("left_female", "en"), "They left");
+        assert_eq!(value("left_female", "ja"), "彼女が退出");
+        assert_eq!(value("left_male", "fr"), "Iel est parti");
+
+        assert_eq!(
+            converter.validate(std::slice::from_ref(&message)).iter().map(|diagnostic| diagnostic.to_string()).collect::<Vec<_>>(),
+            vec![
+                "warning: key 'left' (en): select 'gender' lacks the case 'female' that other locales have, so 'other' is used instead",
+                "warning: key 'left' (ja): select 'gender' adds the case 'female' that 'en' doesn't have",
+                "warning: key 'left' (fr): select 'gender' lacks the case 'male' that other locales have, so 'other' is used instead",
+                "warning: key 'left' (fr): select 'gender' adds the case 'female' that 'en' doesn't have",
+            ]
+        );
+
+        // strict ならフォールバックはエラー
+        let converter = converter_with("en", ConverterOptions {
+            strict_select_cases: true,
+            ..ConverterOptions::default()
+        });
+        let error = converter.convert(vec![message]).unwrap_err();
+        assert_eq!(
+            error,
+            "error: key 'left' (en): select 'gender' lacks the case 'female' that other locales have, so 'other' is used instead"
+        );
+    }
+
+    #[test]
+    fn test_missing_source_language() {
+        let message = single_message("joined", &[
+            ("ja", "{gender, select, male {彼} other {その人}}が参加"),
+            ("fr", "{gender, select, male {Il} other {Iel}} a rejoint"),
+        ]);
+        let converter = |missing_source_handling, fallback_language: Option<&str>| {
+            converter_with("en", ConverterOptions {
+                missing_source_handling,
+                fallback_language: fallback_language.map(str::to_string),
+                ..ConverterOptions::default()
+            })
+        };
+
+        let error = converter(super::MissingSourceHandling::Error, None)
+            .convert(vec![message.clone()])
+            .unwrap_err();
+        assert!(error.starts_with("error: key 'joined': no localization for the source language 'en'"), "{}", error);
+
+        let converter_skip = converter(super::MissingSourceHandling::Skip, None);
+        assert!(converter_skip.convert(vec![message.clone()]).unwrap().strings.is_empty());
+        assert_eq!(
+            converter_skip.validate(std::slice::from_ref(&message))[0].to_string(),
+            "warning: key 'joined': no localization for the source language 'en': the key is left out"
+        );
+
+        // 分割したキーにもフォールバックの訳がレビュー待ちで入る
+        let xcstrings = converter(super::MissingSourceHandling::Fallback, Some("fr"))
+            .convert(vec![message.clone()])
+            .unwrap();
+        let localization = xcstrings.strings.get("joined_male").unwrap().localizations.get("en").unwrap();
+        let string_unit = localization.string_unit.as_ref().unwrap();
+        assert_eq!(string_unit.value, "Il a rejoint");
+        assert_eq!(string_unit.localization_state, crate::xcstrings::LocalizationState::NeedsReview);
+        assert_eq!(xcstrings.strings.get("joined_other").unwrap().localizations.keys().next().unwrap(), "en");
+
+        let error = converter(super::MissingSourceHandling::Fallback, Some("de"))
+            .convert(vec![message])
+            .unwrap_err();
+        assert!(error.ends_with("the fallback language 'de' is missing too"), "{}", error);
+    }
+}
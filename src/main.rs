@@ -24,13 +24,62 @@ struct Args {
     #[arg(long = "xcstrings-version", value_name = "VERSION", default_value = "1.0")]
     xcstrings_version: String,
 
-    /// The localization state for all strings (translated or needs_review)
+    /// The localization state for all strings (translated, needs_review, new or stale)
     #[arg(short, long, value_name = "STATE", default_value = "translated")]
     localization_state: String,
 
+    /// The extraction state for keys that don't set one (manual, extracted_with_value, migrated or stale)
+    #[arg(long, value_name = "STATE", default_value = "manual")]
+    extraction_state: String,
+
     /// Split select elements into separate keys (default: true)
     #[arg(long, action = clap::ArgAction::Set, default_value = "true")]
     split_select_elements: bool,
+
+    /// Fail when a locale lacks a select case another locale has, instead of using its 'other' case (default: false)
+    #[arg(long, action = clap::ArgAction::Set, default_value = "false")]
+    strict_select_cases: bool,
+
+    /// Maximum number of keys a message with several selects may be split into (default: 64)
+    #[arg(long, value_name = "COUNT", default_value = "64")]
+    max_select_combinations: usize,
+
+    /// Append a placeholder legend to each key's comment (default: true)
+    #[arg(long, action = clap::ArgAction::Set, default_value = "true")]
+    placeholder_legend: bool,
+
+    /// How selectordinal arguments are converted (split or cardinal)
+    #[arg(long, value_name = "MODE", default_value = "split")]
+    ordinal_handling: String,
+
+    /// How plurals with offset: are converted (reject or rewrite). With rewrite the app must pass n - offset,
+    /// and exact cases below the offset (e.g. =0 with offset:1) are rejected
+    #[arg(long, value_name = "MODE", default_value = "reject")]
+    plural_offset_handling: String,
+
+    /// How exact plural cases other than =0 and =1 are converted (error, split or fold)
+    #[arg(long, value_name = "MODE", default_value = "error")]
+    exact_match_handling: String,
+
+    /// Translate <b>, <i> and <link> tags into Markdown instead of keeping tags as text (default: false)
+    #[arg(long, action = clap::ArgAction::Set, default_value = "false")]
+    markdown_tags: bool,
+
+    /// Convert the select on this argument into device variations (e.g. "device")
+    #[arg(long, value_name = "NAME")]
+    device_argument: Option<String>,
+
+    /// Emit plural variations directly when the whole message is one plural (default: true)
+    #[arg(long, action = clap::ArgAction::Set, default_value = "true")]
+    whole_message_plurals: bool,
+
+    /// What happens to keys without a source language localization (error, skip or fallback)
+    #[arg(long, value_name = "MODE", default_value = "error")]
+    missing_source_handling: String,
+
+    /// The locale copied into the source language with --missing-source-handling fallback
+    #[arg(long, value_name = "LOCALE")]
+    fallback_language: Option<String>,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -41,17 +90,53 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let messages: models::LocalizableICUStrings = serde_json::from_str(&input_content)?;
 
     // Convert to xcstrings format
-    let mut options = models::ConverterOptions::default();
-    options.localization_state = match args.localization_state.as_str() {
-        "translated" => xcstrings::LocalizationState::Translated,
-        "needs_review" => xcstrings::LocalizationState::NeedsReview,
-        _ => return Err("Invalid localization state. Must be 'translated' or 'needs_review'".into()),
+    let localization_state: xcstrings::LocalizationState = args.localization_state.parse()?;
+    let extraction_state: xcstrings::ExtractionState = args.extraction_state.parse()?;
+    let ordinal_handling = match args.ordinal_handling.as_str() {
+        "split" => models::OrdinalHandling::Split,
+        "cardinal" => models::OrdinalHandling::Cardinal,
+        _ => return Err("Invalid ordinal handling. Must be 'split' or 'cardinal'".into()),
+    };
+    let plural_offset_handling = match args.plural_offset_handling.as_str() {
+        "reject" => models::PluralOffsetHandling::Reject,
+        "rewrite" => models::PluralOffsetHandling::Rewrite,
+        _ => return Err("Invalid plural offset handling. Must be 'reject' or 'rewrite'".into()),
+    };
+    let exact_match_handling = match args.exact_match_handling.as_str() {
+        "error" => models::ExactMatchHandling::Error,
+        "split" => models::ExactMatchHandling::SplitKeys,
+        "fold" => models::ExactMatchHandling::Fold,
+        _ => return Err("Invalid exact match handling. Must be 'error', 'split' or 'fold'".into()),
+    };
+    let missing_source_handling = match args.missing_source_handling.as_str() {
+        "error" => models::MissingSourceHandling::Error,
+        "skip" => models::MissingSourceHandling::Skip,
+        "fallback" => models::MissingSourceHandling::Fallback,
+        _ => return Err("Invalid missing source handling. Must be 'error', 'skip' or 'fallback'".into()),
+    };
+    if missing_source_handling == models::MissingSourceHandling::Fallback && args.fallback_language.is_none() {
+        return Err("Missing source handling 'fallback' requires --fallback-language".into());
+    }
+    let options = models::ConverterOptions {
+        extraction_state,
+        localization_state,
+        split_select_elements: args.split_select_elements,
+        strict_select_cases: args.strict_select_cases,
+        max_select_combinations: args.max_select_combinations,
+        placeholder_legend: args.placeholder_legend,
+        ordinal_handling,
+        plural_offset_handling,
+        exact_match_handling,
+        markdown_tags: args.markdown_tags,
+        device_argument: args.device_argument,
+        whole_message_plurals: args.whole_message_plurals,
+        missing_source_handling,
+        fallback_language: args.fallback_language,
     };
-    options.split_select_elements = args.split_select_elements;
     
-    // Create parser options with ignore_tag always true to prevent HTML tag parsing
+    // Tags are kept as literal text unless they are translated into Markdown
     let parser_options = icu_messageformat_parser::ParserOptions {
-        ignore_tag: true,
+        ignore_tag: !args.markdown_tags,
         requires_other_clause: false,
         should_parse_skeletons: false,
         capture_location: false,
@@ -64,6 +149,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         parser_options,
     );
     let messages: Vec<models::LocalizableICUMessage> = messages.strings.into_iter().map(|s| s.into()).collect();
+
+    let diagnostics = converter.validate(&messages);
+    for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+    if diagnostics.iter().any(|diagnostic| diagnostic.severity == models::Severity::Error) {
+        return Err("Validation failed".into());
+    }
     
     // 並列処理版を使用（デフォルト）
     let mut xcstrings = converter.convert_parallel(messages)?;
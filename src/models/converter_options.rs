@@ -2,19 +2,107 @@ use crate::xcstrings::{ExtractionState, LocalizationState};
 
 #[derive(Clone, Debug)]
 pub struct ConverterOptions {
-    #[allow(dead_code)]
+    /// The extraction state of keys that don't set `extractionState`.
     pub extraction_state: ExtractionState,
+    /// The state of messages that don't set `state`; a message's `state` and
+    /// `variationStates` take precedence.
     pub localization_state: LocalizationState,
     pub split_select_elements: bool,
+    /// Fail when a locale lacks a select case another locale has, instead of
+    /// using its `other` case for that key.
+    pub strict_select_cases: bool,
+    /// Upper bound on the number of keys a message may be split into when it
+    /// contains several select elements.
+    pub max_select_combinations: usize,
+    /// Append a placeholder legend (e.g. `%1$@ = name`) to each key's comment.
+    pub placeholder_legend: bool,
+    /// How `selectordinal` arguments are converted, since String Catalogs only
+    /// have cardinal plural variations.
+    pub ordinal_handling: OrdinalHandling,
+    /// How plurals with `offset:` are converted.
+    pub plural_offset_handling: PluralOffsetHandling,
+    /// How exact plural cases other than `=0` and `=1` are converted.
+    pub exact_match_handling: ExactMatchHandling,
+    /// Translate `<b>`, `<i>` and `<link>` tags into Markdown for SwiftUI.
+    /// Tags are only parsed when the parser's `ignore_tag` is off.
+    pub markdown_tags: bool,
+    /// The argument of a select whose cases are devices (`iphone`, `mac`, ...).
+    /// Such a select becomes `variations.device` instead of being split into keys.
+    pub device_argument: Option<String>,
+    /// Emit `variations.plural` directly on the localization when the whole
+    /// message is one plural, as Xcode does, instead of a `%#@count@` string
+    /// unit with a substitution.
+    pub whole_message_plurals: bool,
+    /// What happens to keys without a localization for the source language.
+    pub missing_source_handling: MissingSourceHandling,
+    /// The locale copied into the source language with `MissingSourceHandling::Fallback`.
+    pub fallback_language: Option<String>,
 }
 
-impl ConverterOptions {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrdinalHandling {
+    /// Split the message into one key per ordinal category (e.g. `key_one`,
+    /// `key_two`), with `#` becoming a number argument. The app picks the key.
+    Split,
+    /// Convert the ordinal into a cardinal plural variation. String Catalogs then
+    /// choose the case by cardinal rules, which `validate` reports as a warning.
+    Cardinal,
+}
+
+impl Default for ConverterOptions {
     #[inline]
-    pub fn default() -> ConverterOptions {
+    fn default() -> ConverterOptions {
         ConverterOptions {
             extraction_state: ExtractionState::Manual,
             localization_state: LocalizationState::Translated,
             split_select_elements: true,
+            strict_select_cases: false,
+            max_select_combinations: 64,
+            placeholder_legend: true,
+            ordinal_handling: OrdinalHandling::Split,
+            plural_offset_handling: PluralOffsetHandling::Reject,
+            exact_match_handling: ExactMatchHandling::Error,
+            markdown_tags: false,
+            device_argument: None,
+            whole_message_plurals: true,
+            missing_source_handling: MissingSourceHandling::Error,
+            fallback_language: None,
         }
     }
-} 
\ No newline at end of file
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PluralOffsetHandling {
+    /// Fail with an error naming the key and locale, since String Catalogs have
+    /// no plural offset.
+    Reject,
+    /// Convert the plural for the offset value: the app passes `n - offset`,
+    /// which is what `#` shows and what the categories select on, and each `=k`
+    /// case becomes `=(k - offset)`. Cases below the offset (e.g. `=0` with
+    /// `offset:1`) would need a negative value and plain `{n}` references would
+    /// show the shifted value, so both are rejected.
+    Rewrite,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExactMatchHandling {
+    /// Fail, since String Catalogs only have `zero` and `one` for exact values.
+    Error,
+    /// Move each `=N` case to its own key with an `_eqN` suffix (e.g. `key_eq2`),
+    /// keeping the remaining cases under the original key.
+    SplitKeys,
+    /// Fold each `=N` case into the category the locale selects for N, which
+    /// `validate` reports as a warning.
+    Fold,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissingSourceHandling {
+    /// Fail, since Xcode shows keys without a source localization as broken.
+    Error,
+    /// Leave keys without a source localization out of the catalog.
+    Skip,
+    /// Copy the `fallback_language` localization into the source language,
+    /// marked `needs_review`. Fails when that locale is missing too.
+    Fallback,
+}
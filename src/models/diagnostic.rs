@@ -0,0 +1,195 @@
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiagnosticKind {
+    /// The locale uses a CLDR plural category that the plural does not provide.
+    MissingPluralCategory { argument: String, category: String },
+    /// The plural provides a category that the locale never selects.
+    UnreachablePluralCategory { argument: String, category: String },
+    /// `zero` or `=0` means something different in ICU than in a String Catalog.
+    ZeroCategorySemantics { argument: String, selector: String, detail: String },
+    /// Literal text contains what looks like a printf format directive.
+    PrintfStyleSequence { sequence: String },
+    /// A `selectordinal` converted with cardinal rules picks the wrong case.
+    OrdinalAsCardinal { argument: String, detail: String },
+    /// A plural `offset:` that can't be converted.
+    PluralOffset { argument: String, offset: i64, detail: String },
+    /// An exact `=N` plural case without a String Catalog variation of its own.
+    ExactMatchCase { argument: String, selector: String, detail: String },
+    /// A tag the conversion can't represent.
+    UnsupportedTag { tag: String, detail: String },
+    /// A message `state` that isn't a String Catalog localization state.
+    UnknownLocalizationState { state: String },
+    /// A key `extractionState` that isn't a String Catalog extraction state.
+    UnknownExtractionState { state: String },
+    /// A placeholder declared with a type the converter doesn't know.
+    UnknownPlaceholderType { placeholder: String, placeholder_type: String },
+    /// A placeholder used in a way its declared type doesn't allow.
+    PlaceholderTypeMismatch { placeholder: String, declared: String, usage: String },
+    /// A declared placeholder that no message uses.
+    UnusedPlaceholder { placeholder: String },
+    /// An argument missing from the key's placeholder declarations.
+    UndeclaredPlaceholder { argument: String },
+    /// Select cases other locales have, which fall back to `other` in this one.
+    MissingSelectCases { argument: String, cases: Vec<String> },
+    /// Select cases this locale has beyond the reference locale.
+    ExtraSelectCases { argument: String, cases: Vec<String>, reference_locale: String },
+    /// A key without a localization for the catalog's source language.
+    MissingSourceLanguage { source_language: String, detail: String },
+}
+
+/// A finding reported by `XCStringConverter::validate` for a single key and locale.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub key: String,
+    pub locale: Option<String>,
+    pub kind: DiagnosticKind,
+}
+
+impl Diagnostic {
+    pub fn warning(key: &str, locale: &str, kind: DiagnosticKind) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            key: key.to_string(),
+            locale: Some(locale.to_string()),
+            kind,
+        }
+    }
+
+    pub fn error(key: &str, locale: &str, kind: DiagnosticKind) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            ..Diagnostic::warning(key, locale, kind)
+        }
+    }
+
+    /// A warning about the key itself rather than one of its locales.
+    pub fn key_warning(key: &str, kind: DiagnosticKind) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            key: key.to_string(),
+            locale: None,
+            kind,
+        }
+    }
+
+    /// An error about the key itself rather than one of its locales.
+    pub fn key_error(key: &str, kind: DiagnosticKind) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            ..Diagnostic::key_warning(key, kind)
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiagnosticKind::MissingPluralCategory { argument, category } => write!(
+                f,
+                "plural '{}' is missing the '{}' category required by this locale",
+                argument, category
+            ),
+            DiagnosticKind::UnreachablePluralCategory { argument, category } => write!(
+                f,
+                "plural '{}' has a '{}' case that this locale never selects",
+                argument, category
+            ),
+            DiagnosticKind::ZeroCategorySemantics { argument, selector, detail } => {
+                write!(f, "plural '{}' case '{}': {}", argument, selector, detail)
+            }
+            DiagnosticKind::PrintfStyleSequence { sequence } => write!(
+                f,
+                "literal text contains the printf-style sequence '{}'; use an ICU argument such as '{{name}}' instead",
+                sequence
+            ),
+            DiagnosticKind::OrdinalAsCardinal { argument, detail } => write!(
+                f,
+                "selectordinal '{}' is converted to a cardinal plural: {}",
+                argument, detail
+            ),
+            DiagnosticKind::PluralOffset { argument, offset, detail } => {
+                write!(f, "plural '{}' with offset:{}: {}", argument, offset, detail)
+            }
+            DiagnosticKind::ExactMatchCase { argument, selector, detail } => {
+                write!(f, "plural '{}' case '{}': {}", argument, selector, detail)
+            }
+            DiagnosticKind::UnsupportedTag { tag, detail } => write!(f, "tag '<{}>': {}", tag, detail),
+            DiagnosticKind::UnknownLocalizationState { state } => write!(
+                f,
+                "unknown localization state '{}'; use translated, needs_review, new or stale",
+                state
+            ),
+            DiagnosticKind::UnknownExtractionState { state } => write!(
+                f,
+                "unknown extraction state '{}'; use manual, extracted_with_value, migrated or stale",
+                state
+            ),
+            DiagnosticKind::UnknownPlaceholderType { placeholder, placeholder_type } => write!(
+                f,
+                "placeholder '{}' has the unknown type '{}'; use String, int, Int32, double or DateTime",
+                placeholder, placeholder_type
+            ),
+            DiagnosticKind::PlaceholderTypeMismatch { placeholder, declared, usage } => write!(
+                f,
+                "placeholder '{}' is declared as {} but used as {}",
+                placeholder, declared, usage
+            ),
+            DiagnosticKind::UnusedPlaceholder { placeholder } => {
+                write!(f, "placeholder '{}' is declared but no message uses it", placeholder)
+            }
+            DiagnosticKind::UndeclaredPlaceholder { argument } => {
+                write!(f, "argument '{}' isn't declared in placeholders", argument)
+            }
+            DiagnosticKind::MissingSelectCases { argument, cases } => write!(
+                f,
+                "select '{}' lacks {} that other locales have, so 'other' is used instead",
+                argument,
+                describe_cases(cases)
+            ),
+            DiagnosticKind::ExtraSelectCases { argument, cases, reference_locale } => write!(
+                f,
+                "select '{}' adds {} that '{}' doesn't have",
+                argument,
+                describe_cases(cases),
+                reference_locale
+            ),
+            DiagnosticKind::MissingSourceLanguage { source_language, detail } => {
+                write!(f, "no localization for the source language '{}': {}", source_language, detail)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.locale {
+            Some(locale) => write!(f, "{}: key '{}' ({}): {}", self.severity, self.key, locale, self.kind),
+            None => write!(f, "{}: key '{}': {}", self.severity, self.key, self.kind),
+        }
+    }
+}
+
+fn describe_cases(cases: &[String]) -> String {
+    let quoted: Vec<String> = cases.iter().map(|case_key| format!("'{}'", case_key)).collect();
+    match quoted.len() {
+        1 => format!("the case {}", quoted[0]),
+        _ => format!("the cases {}", quoted.join(", ")),
+    }
+}
@@ -1,22 +1,110 @@
 use linked_hash_map;
 use serde::Deserialize;
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct LocalizableICUString {
     pub key: String,
     pub messages: linked_hash_map::LinkedHashMap<String, LocalizableICUMessageValue>,
     pub comment: Option<String>,
+    /// `manual`, `extracted_with_value`, `migrated` or `stale`; defaults to the
+    /// converter option.
+    #[serde(rename = "extractionState")]
+    pub extraction_state: Option<String>,
+    #[serde(rename = "shouldTranslate")]
+    pub should_translate: Option<bool>,
+    /// ARB-style declarations of the arguments, keyed by name.
+    pub placeholders: Option<linked_hash_map::LinkedHashMap<String, Placeholder>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Placeholder {
+    /// `String`, `int`, `Int32`, `double` or `DateTime`, see `PlaceholderType`.
+    #[serde(rename = "type")]
+    pub placeholder_type: Option<String>,
+    /// Appended to the key's comment for translators.
+    pub description: Option<String>,
+    pub example: Option<String>,
+}
+
+/// The declared type of a placeholder, which picks its format specifier over
+/// the one the ICU syntax implies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaceholderType {
+    String,
+    Int,
+    Int32,
+    Double,
+    DateTime,
+}
+
+impl PlaceholderType {
+    /// The format specifier (without `%`) for the type.
+    pub fn specifier(&self) -> &'static str {
+        match self {
+            PlaceholderType::String | PlaceholderType::DateTime => "@",
+            PlaceholderType::Int => "lld",
+            PlaceholderType::Int32 => "d",
+            PlaceholderType::Double => "f",
+        }
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, PlaceholderType::Int | PlaceholderType::Int32 | PlaceholderType::Double)
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Deserialize)]
+impl std::str::FromStr for PlaceholderType {
+    type Err = String;
+
+    /// Reads ARB type names case-insensitively, with `num` as a double and
+    /// `Date` as a `DateTime`.
+    fn from_str(placeholder_type: &str) -> Result<PlaceholderType, String> {
+        match placeholder_type.to_ascii_lowercase().as_str() {
+            "string" => Ok(PlaceholderType::String),
+            "int" | "int64" | "integer" => Ok(PlaceholderType::Int),
+            "int32" => Ok(PlaceholderType::Int32),
+            "double" | "float" | "num" => Ok(PlaceholderType::Double),
+            "datetime" | "date" => Ok(PlaceholderType::DateTime),
+            _ => Err(format!(
+                "Invalid placeholder type '{}'. Must be 'String', 'int', 'Int32', 'double' or 'DateTime'",
+                placeholder_type
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for PlaceholderType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlaceholderType::String => write!(f, "String"),
+            PlaceholderType::Int => write!(f, "int"),
+            PlaceholderType::Int32 => write!(f, "Int32"),
+            PlaceholderType::Double => write!(f, "double"),
+            PlaceholderType::DateTime => write!(f, "DateTime"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
 pub struct LocalizableICUMessageValue {
     pub value: String,
-    #[serde(default = "default_localization_state")]
-    pub state: String,
+    /// `translated`, `needs_review`, `new` or `stale`; anything else is rejected.
+    /// Defaults to the converter option.
+    pub state: Option<String>,
+    /// States for single variations, keyed by argument and then by case as
+    /// written in the message (`one`, `=0`, `male`) or by the String Catalog
+    /// variation (`zero`). They take precedence over `state`.
+    #[serde(rename = "variationStates")]
+    pub variation_states: Option<linked_hash_map::LinkedHashMap<String, linked_hash_map::LinkedHashMap<String, String>>>,
 }
 
-fn default_localization_state() -> String {
-    "translated".to_string()
+impl LocalizableICUMessageValue {
+    /// The state set in `variation_states` for the first of `cases` of `argument`
+    /// that has one.
+    pub fn variation_state(&self, argument: &str, cases: &[&str]) -> Option<&String> {
+        let states = self.variation_states.as_ref()?.get(argument)?;
+        cases.iter().find_map(|case| states.get(*case))
+    }
 }
 
 impl From<LocalizableICUString> for LocalizableICUMessage {
@@ -25,6 +113,9 @@ impl From<LocalizableICUString> for LocalizableICUMessage {
             key: string.key,
             messages: string.messages,
             comment: string.comment,
+            extraction_state: string.extraction_state,
+            should_translate: string.should_translate,
+            placeholders: string.placeholders,
         }
     }
 }
@@ -34,9 +125,14 @@ pub struct LocalizableICUStrings {
     pub strings: Vec<LocalizableICUString>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct LocalizableICUMessage {
     pub key: String,
     pub messages: linked_hash_map::LinkedHashMap<String, LocalizableICUMessageValue>,
     pub comment: Option<String>,
+    #[serde(rename = "extractionState")]
+    pub extraction_state: Option<String>,
+    #[serde(rename = "shouldTranslate")]
+    pub should_translate: Option<bool>,
+    pub placeholders: Option<linked_hash_map::LinkedHashMap<String, Placeholder>>,
 } 
\ No newline at end of file
@@ -1,6 +1,8 @@
 pub mod icu_message;
 pub mod converter_options;
+pub mod diagnostic;
 
 // Re-export commonly used types for convenience
 pub use icu_message::*;
-pub use converter_options::*; 
\ No newline at end of file
+pub use converter_options::*;
+pub use diagnostic::*; 
\ No newline at end of file
@@ -12,39 +12,107 @@ pub struct XCStrings {
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct XCString {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
     pub extraction_state: ExtractionState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_comment_auto_generated: Option<bool>,
     pub localizations: LinkedHashMap<String, Localization>,
+    /// Only written as `false`, for keys translators should leave alone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub should_translate: Option<bool>,
 }
 
-#[derive(Clone, Debug, Serialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
 pub enum ExtractionState {
     Manual,
+    ExtractedWithValue,
+    Migrated,
+    Stale,
+}
+
+impl std::str::FromStr for ExtractionState {
+    type Err = String;
+
+    /// Reads the spelling used in String Catalogs and in the input, e.g. `extracted_with_value`.
+    fn from_str(state: &str) -> Result<ExtractionState, String> {
+        match state {
+            "manual" => Ok(ExtractionState::Manual),
+            "extracted_with_value" => Ok(ExtractionState::ExtractedWithValue),
+            "migrated" => Ok(ExtractionState::Migrated),
+            "stale" => Ok(ExtractionState::Stale),
+            _ => Err(format!(
+                "Invalid extraction state '{}'. Must be 'manual', 'extracted_with_value', 'migrated' or 'stale'",
+                state
+            )),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Localization {
-    pub string_unit: StringUnit,
+    /// Absent when the localization only has `variations`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub string_unit: Option<StringUnit>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub substitutions: Option<LinkedHashMap<String, Substitution>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variations: Option<LocalizationVariations>,
 }
 
+/// The device variation keys a String Catalog accepts.
+pub const DEVICE_VARIATIONS: &[&str] = &["iphone", "ipad", "ipod", "mac", "applewatch", "appletv", "applevision", "other"];
+
+/// Variations of a whole localization, as opposed to those of a substitution.
 #[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LocalizationVariations {
+    /// Keyed by `iphone`, `ipad`, `ipod`, `mac`, `applewatch`, `appletv`,
+    /// `applevision` or `other`.
+    Device(LinkedHashMap<String, VariationValue>),
+    /// Used when the whole message is one plural, in place of a `%#@count@`
+    /// string unit and its substitution.
+    Plural(LinkedHashMap<String, VariationValue>),
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
 pub struct StringUnit {
     #[serde(rename = "state")]
     pub localization_state: LocalizationState,
     pub value: String,
 }
 
-#[derive(Clone, Debug, Serialize, PartialEq)]
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum LocalizationState {
+    #[default]
     Translated,
     NeedsReview,
+    New,
+    Stale,
 }
 
-#[derive(Clone, Debug, Serialize)]
+impl std::str::FromStr for LocalizationState {
+    type Err = String;
+
+    /// Reads the spelling used in String Catalogs and in the input, e.g. `needs_review`.
+    fn from_str(state: &str) -> Result<LocalizationState, String> {
+        match state {
+            "translated" => Ok(LocalizationState::Translated),
+            "needs_review" => Ok(LocalizationState::NeedsReview),
+            "new" => Ok(LocalizationState::New),
+            "stale" => Ok(LocalizationState::Stale),
+            _ => Err(format!(
+                "Invalid localization state '{}'. Must be 'translated', 'needs_review', 'new' or 'stale'",
+                state
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Substitution {
     pub arg_num: usize,
@@ -52,14 +120,14 @@ pub struct Substitution {
     pub variations: VariationType,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum VariationType {
     Plural(LinkedHashMap<String, VariationValue>),
     Select(LinkedHashMap<String, VariationValue>),
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct VariationValue {
     pub string_unit: StringUnit,
@@ -81,6 +149,54 @@ mod tests {
         let needs_review = LocalizationState::NeedsReview;
         let json = serde_json::to_string(&needs_review).unwrap();
         assert_eq!(json, "\"needs_review\"");
+
+        assert_eq!(serde_json::to_string(&LocalizationState::New).unwrap(), "\"new\"");
+        assert_eq!(serde_json::to_string(&LocalizationState::Stale).unwrap(), "\"stale\"");
+    }
+
+    #[test]
+    fn test_extraction_state_serialization() {
+        assert_eq!(serde_json::to_string(&ExtractionState::Manual).unwrap(), "\"manual\"");
+        assert_eq!(
+            serde_json::to_string(&ExtractionState::ExtractedWithValue).unwrap(),
+            "\"extracted_with_value\""
+        );
+        assert_eq!(serde_json::to_string(&ExtractionState::Migrated).unwrap(), "\"migrated\"");
+        assert_eq!(serde_json::to_string(&ExtractionState::Stale).unwrap(), "\"stale\"");
+    }
+
+    #[test]
+    fn test_state_parsing() {
+        assert_eq!("needs_review".parse(), Ok(LocalizationState::NeedsReview));
+        assert_eq!("stale".parse(), Ok(LocalizationState::Stale));
+        assert!("needsReview".parse::<LocalizationState>().is_err());
+        assert_eq!("extracted_with_value".parse(), Ok(ExtractionState::ExtractedWithValue));
+        assert!("automatic".parse::<ExtractionState>().is_err());
+    }
+
+    #[test]
+    fn test_device_variations_serialization() {
+        let mut devices = LinkedHashMap::new();
+        devices.insert(
+            "iphone".to_string(),
+            VariationValue {
+                string_unit: StringUnit {
+                    localization_state: LocalizationState::Translated,
+                    value: "Tap".to_string(),
+                },
+            },
+        );
+        let localization = Localization {
+            string_unit: None,
+            substitutions: None,
+            variations: Some(LocalizationVariations::Device(devices)),
+        };
+
+        let json = serde_json::to_string(&localization).unwrap();
+        assert_eq!(
+            json,
+            r#"{"variations":{"device":{"iphone":{"stringUnit":{"state":"translated","value":"Tap"}}}}}"#
+        );
     }
 
     #[test]
@@ -1,7 +1,10 @@
-use std::{fs, path::PathBuf};
+use std::{fs, path::{Path, PathBuf}};
 
-use rust_icu_messageformat_string_catalog_converter::models::{LocalizableICUMessage, LocalizableICUStrings};
+use rust_icu_messageformat_string_catalog_converter::models::{
+    ConverterOptions, ExactMatchHandling, LocalizableICUMessage, LocalizableICUStrings, PluralOffsetHandling,
+};
 use rust_icu_messageformat_string_catalog_converter::converter::XCStringConverter;
+use rust_icu_messageformat_string_catalog_converter::xcstrings::{LocalizationState, XCStrings};
 use testing::fixture;
 
 #[fixture("tests/fixtures/simple_argument")]
@@ -10,10 +13,74 @@ use testing::fixture;
 #[fixture("tests/fixtures/icu_messageformat")]
 #[fixture("tests/fixtures/multiple_arguments")]
 #[fixture("tests/fixtures/select_splitting")]
+#[fixture("tests/fixtures/multiple_plurals")]
+#[fixture("tests/fixtures/multiple_selects")]
+#[fixture("tests/fixtures/select_with_plural")]
+#[fixture("tests/fixtures/select_in_plural")]
+#[fixture("tests/fixtures/selectordinal")]
+#[fixture("tests/fixtures/number_styles")]
+#[fixture("tests/fixtures/nested_plurals")]
+#[fixture("tests/fixtures/placeholders")]
 fn converter_tests(dir: PathBuf) {
-    let input_path = dir.join("input.json");
+    assert_fixture_output(&dir, ConverterOptions::default());
+}
+
+#[fixture("tests/fixtures/localization_states")]
+fn default_localization_state_tests(dir: PathBuf) {
+    let options = ConverterOptions {
+        localization_state: LocalizationState::NeedsReview,
+        ..Default::default()
+    };
+    assert_fixture_output(&dir, options);
+}
+
+#[fixture("tests/fixtures/plural_offset_rewrite")]
+fn rewrite_plural_offset_tests(dir: PathBuf) {
+    let options = ConverterOptions {
+        plural_offset_handling: PluralOffsetHandling::Rewrite,
+        ..Default::default()
+    };
+    assert_fixture_output(&dir, options);
+}
+
+#[fixture("tests/fixtures/exact_match_split")]
+fn split_exact_match_tests(dir: PathBuf) {
+    let options = ConverterOptions {
+        exact_match_handling: ExactMatchHandling::SplitKeys,
+        ..Default::default()
+    };
+    assert_fixture_output(&dir, options);
+}
+
+#[fixture("tests/fixtures/markdown_tags")]
+fn markdown_tag_tests(dir: PathBuf) {
+    let options = ConverterOptions {
+        markdown_tags: true,
+        ..Default::default()
+    };
+    assert_fixture_output(&dir, options);
+}
+
+#[fixture("tests/fixtures/device_variations")]
+fn device_variation_tests(dir: PathBuf) {
+    let options = ConverterOptions {
+        device_argument: Some("device".to_string()),
+        ..Default::default()
+    };
+    assert_fixture_output(&dir, options);
+}
+
+// expected_error.txt があるフィクスチャは変換が失敗することを確認する
+#[fixture("tests/fixtures/plural_offset_reject")]
+fn converter_error_tests(dir: PathBuf) {
+    let expected_error = fs::read_to_string(dir.join("expected_error.txt")).expect("Failed to read expected_error.txt");
+    let error = convert_fixture(&dir, "en".to_string(), ConverterOptions::default())
+        .expect_err("Expected conversion to fail");
+    similar_asserts::assert_eq!(error, expected_error.trim().to_string());
+}
+
+fn assert_fixture_output(dir: &Path, options: ConverterOptions) {
     let expected_path = dir.join("expected_output.json");
-    let message = fs::read_to_string(&input_path).expect("Failed to read input.json");
     let output = fs::read_to_string(&expected_path).expect("Failed to read expected_output.json");
 
     // source_languageはinput.jsonやexpected_output.jsonから取得する必要がある場合はパースする
@@ -21,20 +88,26 @@ fn converter_tests(dir: PathBuf) {
     let expected_json: serde_json::Value = serde_json::from_str(&output).expect("Failed to parse expected_output.json");
     let source_language = expected_json["sourceLanguage"].as_str().unwrap_or("").to_string();
 
+    let result = convert_fixture(dir, source_language, options).expect("Failed to convert messages");
+    let result_json_string = serde_json::to_string_pretty(&result)
+        .expect("Failed to serialize result to JSON")
+        .trim()
+        .to_string();
+    similar_asserts::assert_eq!(result_json_string, output.trim().to_string());
+}
+
+fn convert_fixture(dir: &Path, source_language: String, options: ConverterOptions) -> Result<XCStrings, String> {
+    let input_path = dir.join("input.json");
+    let message = fs::read_to_string(&input_path).expect("Failed to read input.json");
     let messages: LocalizableICUStrings = serde_json::from_str(&message)
         .expect("Failed to parse test fixture message JSON");
     let converter = XCStringConverter::new(
         source_language,
-        rust_icu_messageformat_string_catalog_converter::models::ConverterOptions::default(),
+        options,
         icu_messageformat_parser::ParserOptions::default(),
     );
     let messages: Vec<LocalizableICUMessage> = messages.strings.into_iter().map(|s| s.into()).collect();
-    let result = converter.convert(messages).expect("Failed to convert messages");
-    let result_json_string = serde_json::to_string_pretty(&result)
-        .expect("Failed to serialize result to JSON")
-        .trim()
-        .to_string();
-    similar_asserts::assert_eq!(result_json_string, output.trim().to_string());
+    converter.convert(messages)
 }
 
 #[test]
@@ -53,8 +126,10 @@ fn test_select_error_case() {
     
     let messages: LocalizableICUStrings = serde_json::from_str(messages_json)
         .expect("Failed to parse test JSON");
-    let mut options = rust_icu_messageformat_string_catalog_converter::models::ConverterOptions::default();
-    options.split_select_elements = false;
+    let options = ConverterOptions {
+        split_select_elements: false,
+        ..Default::default()
+    };
     
     let converter = XCStringConverter::new(
         "en".to_string(),
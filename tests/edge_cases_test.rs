@@ -1,5 +1,5 @@
 use rust_icu_messageformat_string_catalog_converter::models::{
-    LocalizableICUMessage, LocalizableICUMessageValue, LocalizableICUStrings, ConverterOptions
+    LocalizableICUMessage, LocalizableICUMessageValue, LocalizableICUStrings, ConverterOptions, MissingSourceHandling
 };
 use rust_icu_messageformat_string_catalog_converter::converter::XCStringConverter;
 use linked_hash_map::LinkedHashMap;
@@ -29,13 +29,15 @@ fn test_empty_string_values() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: "".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "empty_value".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -49,7 +51,7 @@ fn test_empty_string_values() {
     let xcstrings = result.unwrap();
     assert_eq!(xcstrings.strings.len(), 1);
     assert_eq!(xcstrings.strings.get("empty_value").unwrap()
-        .localizations.get("en").unwrap().string_unit.value, "");
+        .localizations.get("en").unwrap().string_unit.as_ref().unwrap().value, "");
 }
 
 // テスト3: 空のメッセージマップ
@@ -59,6 +61,7 @@ fn test_empty_messages_map() {
         key: "no_messages".to_string(),
         messages: LinkedHashMap::new(),
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -67,11 +70,20 @@ fn test_empty_messages_map() {
         icu_messageformat_parser::ParserOptions::default(),
     );
     
-    let result = converter.convert(vec![message]);
-    assert!(result.is_ok());
-    let xcstrings = result.unwrap();
-    assert_eq!(xcstrings.strings.len(), 1);
-    assert_eq!(xcstrings.strings.get("no_messages").unwrap().localizations.len(), 0);
+    // ソース言語がないキーはエラー
+    let result = converter.convert(vec![message.clone()]);
+    assert!(result.unwrap_err().contains("no localization for the source language 'en'"));
+
+    let converter = XCStringConverter::new(
+        "en".to_string(),
+        ConverterOptions {
+            missing_source_handling: MissingSourceHandling::Skip,
+            ..ConverterOptions::default()
+        },
+        icu_messageformat_parser::ParserOptions::default(),
+    );
+    let xcstrings = converter.convert(vec![message]).unwrap();
+    assert!(xcstrings.strings.is_empty());
 }
 
 // テスト4: 非常に長い文字列
@@ -81,13 +93,15 @@ fn test_very_long_strings() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: format!("Hello {{name}}, {}", very_long_string),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "long_string".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -106,13 +120,15 @@ fn test_invalid_icu_format() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: "Hello {unclosed_bracket".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "invalid_format".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -138,13 +154,15 @@ fn test_many_variables() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: message_value,
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "many_vars".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -163,13 +181,15 @@ fn test_special_characters_escaped() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: "Hello {name}! 🎉 Special chars: @#$%^&*()[]'{'}'|\\\"''<>?/~`".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "special_chars_escaped".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -188,13 +208,15 @@ fn test_special_characters_unescaped() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: "Hello {name}! 🎉 Special chars: @#$%^&*()[]{}|\\\"'<>?/~`".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "special_chars_unescaped".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -215,13 +237,15 @@ fn test_unicode_key_names() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: "Hello world".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "キー_🔑_ключ".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -240,25 +264,29 @@ fn test_duplicate_keys() {
     let mut messages1 = LinkedHashMap::new();
     messages1.insert("en".to_string(), LocalizableICUMessageValue {
         value: "First value".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let mut messages2 = LinkedHashMap::new();
     messages2.insert("en".to_string(), LocalizableICUMessageValue {
         value: "Second value".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message1 = LocalizableICUMessage {
         key: "duplicate".to_string(),
         messages: messages1,
         comment: None,
+        ..Default::default()
     };
     
     let message2 = LocalizableICUMessage {
         key: "duplicate".to_string(),
         messages: messages2,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -272,7 +300,7 @@ fn test_duplicate_keys() {
     // 後の値で上書きされる
     let xcstrings = result.unwrap();
     assert_eq!(xcstrings.strings.get("duplicate").unwrap()
-        .localizations.get("en").unwrap().string_unit.value, "Second value");
+        .localizations.get("en").unwrap().string_unit.as_ref().unwrap().value, "Second value");
 }
 
 // テスト10: 無効なlocalization state
@@ -281,13 +309,15 @@ fn test_invalid_localization_state() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: "Hello world".to_string(),
-        state: "invalid_state".to_string(),
+        state: Some("invalid_state".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "invalid_state_key".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -296,13 +326,15 @@ fn test_invalid_localization_state() {
         icu_messageformat_parser::ParserOptions::default(),
     );
     
+    // 未知の state は translated として扱わずにエラーにする
+    let diagnostics = converter.validate(std::slice::from_ref(&message));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, rust_icu_messageformat_string_catalog_converter::models::Severity::Error);
+    assert_eq!(diagnostics[0].locale.as_deref(), Some("en"));
+
     let result = converter.convert(vec![message]);
-    assert!(result.is_ok());
-    // デフォルトで"translated"になる
-    let xcstrings = result.unwrap();
-    assert_eq!(xcstrings.strings.get("invalid_state_key").unwrap()
-        .localizations.get("en").unwrap().string_unit.localization_state,
-        rust_icu_messageformat_string_catalog_converter::xcstrings::LocalizationState::Translated);
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("Invalid localization state 'invalid_state'"));
 }
 
 // テスト11: select要素でother case不足
@@ -311,13 +343,15 @@ fn test_select_without_other_case() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: "{gender, select, male {He} female {She}}".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "no_other_case".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -328,8 +362,8 @@ fn test_select_without_other_case() {
     
     let result = converter.convert(vec![message]);
     // ICUパーサーが処理するかもしれないが、通常はエラーになる可能性がある
-    if result.is_err() {
-        println!("Select without other case error: {}", result.unwrap_err());
+    if let Err(error) = result {
+        println!("Select without other case error: {}", error);
     } else {
         println!("Select without other case succeeded unexpectedly");
     }
@@ -341,13 +375,15 @@ fn test_empty_select_options() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: "Status: {status, select}".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "empty_select".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -358,8 +394,8 @@ fn test_empty_select_options() {
     
     let result = converter.convert(vec![message]);
     // パーサーがエラーを出すはず
-    if result.is_err() {
-        println!("Empty select options error: {}", result.unwrap_err());
+    if let Err(error) = result {
+        println!("Empty select options error: {}", error);
     } else {
         println!("Empty select options succeeded unexpectedly");
     }
@@ -371,13 +407,15 @@ fn test_deeply_nested_elements() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: "{count, plural, one {{gender, select, male {He has one item} female {She has one item} other {They have one item}}} other {{gender, select, male {He has # items} female {She has # items} other {They have # items}}}}".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "deeply_nested".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -386,12 +424,19 @@ fn test_deeply_nested_elements() {
         icu_messageformat_parser::ParserOptions::default(),
     );
     
-    let result = converter.convert(vec![message]);
-    if result.is_err() {
-        println!("Deeply nested error: {}", result.unwrap_err());
-    } else {
-        println!("Deeply nested succeeded");
-    }
+    // plural の分岐の中の select はキーごとに分割され、plural だけが残る
+    let xcstrings = converter.convert(vec![message]).unwrap();
+    let keys: Vec<&String> = xcstrings.strings.keys().collect();
+    assert_eq!(keys, vec!["deeply_nested_male", "deeply_nested_female", "deeply_nested_other"]);
+
+    let en = xcstrings.strings.get("deeply_nested_female").unwrap()
+        .localizations.get("en").unwrap();
+    // メッセージ全体が plural なので、plural のバリエーションが直接出力される
+    assert!(en.string_unit.is_none());
+    let variations = serde_json::to_value(en).unwrap();
+    assert_eq!(variations["variations"]["plural"]["one"]["stringUnit"]["value"], "She has one item");
+    // ICU は入れ子の select の中の # を数値に置き換えないため、そのまま文字として残る
+    assert_eq!(variations["variations"]["plural"]["other"]["stringUnit"]["value"], "She has # items");
 }
 
 // テスト14: ゼロ長の変数名
@@ -400,13 +445,15 @@ fn test_empty_variable_name() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: "Hello {}!".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "empty_var_name".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -416,8 +463,8 @@ fn test_empty_variable_name() {
     );
     
     let result = converter.convert(vec![message]);
-    if result.is_err() {
-        println!("Empty variable name error: {}", result.unwrap_err());
+    if let Err(error) = result {
+        println!("Empty variable name error: {}", error);
     } else {
         println!("Empty variable name succeeded");
     }
@@ -436,13 +483,15 @@ fn test_position_overflow() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: message_value,
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "overflow_test".to_string(),
         messages,
         comment: None,
+        ..Default::default()
     };
     
     let converter = XCStringConverter::new(
@@ -461,13 +510,15 @@ fn test_html_xml_tags_not_supported() {
     let mut messages = LinkedHashMap::new();
     messages.insert("en".to_string(), LocalizableICUMessageValue {
         value: "For health insurance cards only: Please hide <symbol and number> and <insurer number> before submitting.".to_string(),
-        state: "translated".to_string(),
+        state: Some("translated".to_string()),
+        ..Default::default()
     });
     
     let message = LocalizableICUMessage {
         key: "html_tag_test".to_string(),
         messages,
         comment: Some("Test case with angle brackets that should be treated as literal text".to_string()),
+        ..Default::default()
     };
     
     // デフォルトのパーサーオプション（ignore_tag: true）で処理
@@ -490,7 +541,7 @@ fn test_html_xml_tags_not_supported() {
     
     if let Ok(xcstrings) = result {
         let localization = xcstrings.strings.get("html_tag_test").unwrap();
-        let en_value = &localization.localizations.get("en").unwrap().string_unit.value;
+        let en_value = &localization.localizations.get("en").unwrap().string_unit.as_ref().unwrap().value;
         // 角括弧がそのまま保持されることを確認（HTMLタグとして解釈されない）
         assert!(en_value.contains("<symbol and number>"));
         assert!(en_value.contains("<insurer number>"));
@@ -515,13 +566,15 @@ fn test_common_html_tags_preserved_as_text() {
         let mut messages = LinkedHashMap::new();
         messages.insert("en".to_string(), LocalizableICUMessageValue {
             value: value.to_string(),
-            state: "translated".to_string(),
+            state: Some("translated".to_string()),
+            ..Default::default()
         });
         
         let message = LocalizableICUMessage {
             key: key.to_string(),
             messages,
             comment: Some("Test case with HTML tags that should be preserved as literal text".to_string()),
+            ..Default::default()
         };
         
         let parser_options = icu_messageformat_parser::ParserOptions {
@@ -543,7 +596,7 @@ fn test_common_html_tags_preserved_as_text() {
         
         if let Ok(xcstrings) = result {
             let localization = xcstrings.strings.get(key).unwrap();
-            let en_value = &localization.localizations.get("en").unwrap().string_unit.value;
+            let en_value = &localization.localizations.get("en").unwrap().string_unit.as_ref().unwrap().value;
             // 元の文字列がそのまま保持されることを確認
             assert_eq!(en_value, value, "HTML tags were not preserved as literal text for case: {}", key);
         }
@@ -0,0 +1,102 @@
+use rust_icu_messageformat_string_catalog_converter::models::{
+    LocalizableICUMessage, LocalizableICUMessageValue, ConverterOptions
+};
+use rust_icu_messageformat_string_catalog_converter::converter::XCStringConverter;
+use rust_icu_messageformat_string_catalog_converter::xcstrings::{XCString, Substitution};
+use linked_hash_map::LinkedHashMap;
+
+fn convert(key: &str, values: &[(&str, &str)]) -> XCString {
+    let mut messages = LinkedHashMap::new();
+    for (locale, value) in values {
+        messages.insert(locale.to_string(), LocalizableICUMessageValue {
+            value: value.to_string(),
+            state: Some("translated".to_string()),
+            ..Default::default()
+        });
+    }
+
+    let message = LocalizableICUMessage {
+        key: key.to_string(),
+        messages,
+        comment: None,
+        ..Default::default()
+    };
+
+    let converter = XCStringConverter::new(
+        "en".to_string(),
+        ConverterOptions::default(),
+        icu_messageformat_parser::ParserOptions::default(),
+    );
+
+    let mut xcstrings = converter.convert(vec![message]).unwrap();
+    xcstrings.strings.remove(key).unwrap()
+}
+
+fn substitution<'a>(xcstring: &'a XCString, locale: &str, name: &str) -> &'a Substitution {
+    xcstring.localizations.get(locale).unwrap()
+        .substitutions.as_ref().unwrap()
+        .get(name).unwrap()
+}
+
+// テスト1: 複数の plural はそれぞれ出現順の位置を持つ
+#[test]
+fn test_two_plurals_get_distinct_positions() {
+    let xcstring = convert("files_in_folders", &[
+        ("en", "{files, plural, one {# file} other {# files}} in {folders, plural, one {# folder} other {# folders}}"),
+    ]);
+
+    let en = xcstring.localizations.get("en").unwrap();
+    assert_eq!(en.string_unit.as_ref().unwrap().value, "%#@files@ in %#@folders@");
+    assert_eq!(substitution(&xcstring, "en", "files").arg_num, 1);
+    assert_eq!(substitution(&xcstring, "en", "folders").arg_num, 2);
+    assert_eq!(substitution(&xcstring, "en", "files").format_specifier, "lld");
+    assert_eq!(substitution(&xcstring, "en", "folders").format_specifier, "lld");
+}
+
+// テスト2: plural と通常の引数が同じ位置の割り当てを共有する
+#[test]
+fn test_plurals_mixed_with_plain_arguments() {
+    let xcstring = convert("upload_summary", &[
+        ("en", "{name} uploaded {files, plural, one {# file} other {# files}} to {album} in {folders, plural, one {# folder} other {# folders}}"),
+    ]);
+
+    let en = xcstring.localizations.get("en").unwrap();
+    assert_eq!(en.string_unit.as_ref().unwrap().value, "%1$@ uploaded %#@files@ to %3$@ in %#@folders@");
+    assert_eq!(substitution(&xcstring, "en", "files").arg_num, 2);
+    assert_eq!(substitution(&xcstring, "en", "folders").arg_num, 4);
+    assert_eq!(
+        xcstring.comment.as_deref(),
+        Some("%1$@ = name, %2$lld = files, %3$@ = album, %4$lld = folders")
+    );
+}
+
+// テスト3: 翻訳で順序が変わっても原文の位置を使う
+#[test]
+fn test_reordered_plurals_keep_source_positions() {
+    let xcstring = convert("upload_summary", &[
+        ("en", "{name} uploaded {files, plural, one {# file} other {# files}} in {folders, plural, one {# folder} other {# folders}}"),
+        ("ja", "{name}が{folders, plural, other {#個のフォルダ}}に{files, plural, other {#個のファイル}}をアップロードしました"),
+    ]);
+
+    let ja = xcstring.localizations.get("ja").unwrap();
+    assert_eq!(ja.string_unit.as_ref().unwrap().value, "%1$@が%#@folders@に%#@files@をアップロードしました");
+    assert_eq!(substitution(&xcstring, "ja", "files").arg_num, 2);
+    assert_eq!(substitution(&xcstring, "ja", "folders").arg_num, 3);
+}
+
+// テスト4: plural の分岐内の引数は文字列全体の位置を使う
+#[test]
+fn test_arguments_inside_branches_share_positions() {
+    let xcstring = convert("shared_by", &[
+        ("en", "{files, plural, one {# file from {owner}} other {# files from {owner}}} in {folders, plural, one {# folder} other {# folders by {owner}}}"),
+    ]);
+
+    assert_eq!(substitution(&xcstring, "en", "files").arg_num, 1);
+    assert_eq!(substitution(&xcstring, "en", "folders").arg_num, 3);
+    let json = serde_json::to_value(substitution(&xcstring, "en", "folders")).unwrap();
+    assert_eq!(json["variations"]["plural"]["other"]["stringUnit"]["value"], "%arg folders by %2$@");
+    assert_eq!(
+        xcstring.comment.as_deref(),
+        Some("%1$lld = files, %2$@ = owner, %3$lld = folders")
+    );
+}